@@ -0,0 +1,170 @@
+//! Compares the interpreter against programs translated to Rust by `intcode aot`, on day19's
+//! 50x50 scan and day23's network. Run with `cargo bench --features part-one --bench aot`.
+//!
+//! The translations were made with:
+//!
+//!     intcode aot inputs/day19.txt 3 4 > benches/aot/day19.rs
+//!     intcode aot inputs/day23.txt 0 -1 -1 -1 > benches/aot/day23.rs
+use adventofcode2019::intcode::{Event, Program, Queue, I};
+use std::time::{Duration, Instant};
+
+// Generated files are checked in exactly as `intcode aot` writes them.
+#[rustfmt::skip]
+#[path = "aot/day19.rs"]
+mod day19;
+#[rustfmt::skip]
+#[path = "aot/day23.rs"]
+mod day23;
+
+const ROUNDS: u32 = 5;
+
+fn main() {
+  let day19 = load(include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/inputs/day19.txt"
+  )));
+  let day23 = load(include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/inputs/day23.txt"
+  )));
+  compare(
+    "day19 50x50 scan",
+    || scan(day19.clone()),
+    || scan(day19::Compiled::new(day19.clone())),
+  );
+  compare(
+    "day23 network",
+    || network(|| day23.clone()),
+    || network(|| day23::Compiled::new(day23.clone())),
+  );
+}
+
+fn load(input: &str) -> Program {
+  input.parse().unwrap()
+}
+
+/// The interpreter and the translations, run the same way.
+trait Run {
+  fn program(&mut self) -> &mut Program;
+  fn run_to_event(&mut self) -> Event;
+  fn reset(&mut self);
+}
+
+impl Run for Program {
+  fn program(&mut self) -> &mut Program {
+    self
+  }
+  fn run_to_event(&mut self) -> Event {
+    Program::run_to_event(self).unwrap()
+  }
+  fn reset(&mut self) {
+    Program::reset(self)
+  }
+}
+
+macro_rules! run_compiled {
+  ($($m:ident),*) => {
+    $(
+      impl Run for $m::Compiled<Queue> {
+        fn program(&mut self) -> &mut Program {
+          &mut self.program
+        }
+        fn run_to_event(&mut self) -> Event {
+          $m::Compiled::run_to_event(self).unwrap()
+        }
+        fn reset(&mut self) {
+          $m::Compiled::reset(self)
+        }
+      }
+    )*
+  };
+}
+
+run_compiled!(day19, day23);
+
+fn compare(name: &str, interpreted: impl Fn() -> I, compiled: impl Fn() -> I) {
+  let (interpreted_answer, interpreted_time) = time(interpreted);
+  let (compiled_answer, compiled_time) = time(compiled);
+  assert_eq!(interpreted_answer, compiled_answer);
+  println!(
+    "{:<20} interpreted: {:>10.2?}  compiled: {:>10.2?}  speedup: {:.2}x",
+    name,
+    interpreted_time,
+    compiled_time,
+    interpreted_time.as_secs_f64() / compiled_time.as_secs_f64()
+  );
+}
+
+/// Best of `ROUNDS` runs.
+fn time(f: impl Fn() -> I) -> (I, Duration) {
+  (0..ROUNDS)
+    .map(|_| {
+      let start = Instant::now();
+      let answer = f();
+      (answer, start.elapsed())
+    })
+    .min_by_key(|(_, elapsed)| *elapsed)
+    .unwrap()
+}
+
+/// Counts the points affected by the tractor beam, resetting the program for each one.
+fn scan(mut program: impl Run) -> I {
+  let mut affected = 0;
+  for y in 0..50 {
+    for x in 0..50 {
+      program.reset();
+      program.program().system.input.extend(&[x, y]);
+      while program.run_to_event() != Event::Halted {}
+      affected += program.program().system.output[0];
+    }
+  }
+  affected
+}
+
+/// Runs 50 NICs round robin with a NAT that wakes NIC 0 whenever a round passes without
+/// traffic, returning the first y value the NAT delivers twice in a row.
+fn network<R: Run>(nic: impl Fn() -> R) -> I {
+  let mut nics = (0..50)
+    .map(|i| {
+      let mut nic = nic();
+      nic.program().system.input.push_back(i);
+      nic
+    })
+    .collect::<Vec<_>>();
+  let (mut nat, mut last_delivered) = (None, None);
+  loop {
+    let mut idle = true;
+    for n in 0..nics.len() {
+      let input = &mut nics[n].program().system.input;
+      if input.is_empty() {
+        input.push_back(-1);
+      }
+      while nics[n].run_to_event() != Event::AskForInputAgain {}
+      let output = nics[n]
+        .program()
+        .system
+        .output
+        .drain(..)
+        .collect::<Vec<_>>();
+      for packet in output.chunks(3) {
+        idle = false;
+        match packet {
+          [255, x, y] => nat = Some((*x, *y)),
+          [address, x, y] => nics[*address as usize]
+            .program()
+            .system
+            .input
+            .extend(&[*x, *y]),
+          _ => unreachable!(),
+        }
+      }
+    }
+    if let (true, Some((x, y))) = (idle, nat) {
+      if last_delivered == Some(y) {
+        return y;
+      }
+      last_delivered = Some(y);
+      nics[0].program().system.input.extend(&[x, y]);
+    }
+  }
+}
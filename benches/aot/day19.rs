@@ -0,0 +1,553 @@
+//! Translated from Intcode by `intcode aot`. Do not edit.
+#![allow(dead_code, clippy::all)]
+use adventofcode2019::intcode::{Event, IntcodeError, Program, System, I};
+
+/// Runs a `Program` through the translated blocks, handing any address that was not
+/// translated to the interpreter one instruction at a time.
+///
+/// Blocks whose code has been overwritten are interpreted from then on, apart from operands
+/// the program itself patches, which are read from memory. Everything is interpreted when
+/// checked arithmetic, limits, profiling or coverage are on, since the translation implements
+/// none of them.
+pub struct Compiled<S: System<Word = I>> {
+  pub program: Program<S>,
+  interpreted: bool,
+  stale: Vec<bool>,
+}
+
+impl<S: System<Word = I>> Compiled<S> {
+  pub fn new(program: Program<S>) -> Self {
+    let mut compiled = Self {
+      program,
+      interpreted: false,
+      stale: vec![],
+    };
+    compiled.check();
+    compiled
+  }
+  pub fn reset(&mut self) {
+    self.program.reset();
+    self.check();
+  }
+  /// Same as `Program::run`.
+  pub fn run(&mut self) -> Result<Option<I>, IntcodeError> {
+    loop {
+      match self.run_to_event()? {
+        Event::Halted | Event::AskForInputAgain => break,
+        _ => {}
+      }
+    }
+    let output = self.program.system.end();
+    if self.program.reset_after_run {
+      self.reset();
+    }
+    Ok(output)
+  }
+  /// Same as `Program::run_to_event`.
+  pub fn run_to_event(&mut self) -> Result<Event, IntcodeError> {
+    loop {
+      let event = if self.interpreted {
+        self.interpret()
+      } else {
+        self.block().map_err(|e| {
+          self.program.position = e.address();
+          e
+        })
+      };
+      match event? {
+        Some(Event::Input(_)) | Some(Event::Output(_)) | None => {}
+        Some(e) => return Ok(e),
+      }
+    }
+  }
+  fn check(&mut self) {
+    let program = &self.program;
+    self.interpreted = program.checked
+      || program.limits.is_some()
+      || program.profile.is_some()
+      || program.coverage.is_some();
+    self.stale = BLOCKS
+      .iter()
+      .map(|&(start, end)| (start..end).any(|a| is_code(a) && program.codes.get(a) != IMAGE[a]))
+      .collect();
+  }
+  /// Executes one instruction in the interpreter, noticing if it overwrites code.
+  fn interpret(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let written = self
+      .program
+      .opcode()
+      .ok()
+      .and_then(|opcode| opcode.destination())
+      .and_then(|p| self.program.destination(&p).ok())
+      .filter(|address| is_code(*address));
+    let old = written.map(|address| self.program.codes.get(address));
+    let event = self.program.step()?;
+    if let (Some(address), Some(old)) = (written, old) {
+      if self.program.codes.get(address) != old {
+        self.invalidate(address);
+      }
+    }
+    Ok(event)
+  }
+  fn invalidate(&mut self, address: usize) {
+    for (n, &(start, end)) in BLOCKS.iter().enumerate() {
+      if start <= address && address < end {
+        self.stale[n] = true;
+      }
+    }
+  }
+  fn relative(&self, at: usize, offset: I) -> Result<usize, IntcodeError> {
+    self.address(at, self.program.relative_position.wrapping_add(offset))
+  }
+  fn address(&self, at: usize, target: I) -> Result<usize, IntcodeError> {
+    if target < 0 {
+      return Err(IntcodeError::NegativeAddress {
+        address: at,
+        target,
+      });
+    }
+    Ok(target as usize)
+  }
+  /// Writes to an address the translation knows is not code.
+  fn set(&mut self, address: usize, value: I) {
+    self.program.codes.set(address, value);
+    if let Some(cache) = &mut self.program.cache {
+      cache.invalidate(address);
+    }
+  }
+  /// Writes anywhere, returning whether translated code changed.
+  fn store(&mut self, address: usize, value: I) -> bool {
+    let changed = is_code(address) && self.program.codes.get(address) != value;
+    if changed {
+      self.invalidate(address);
+    }
+    self.set(address, value);
+    changed
+  }
+  fn input(&mut self, at: usize, destination: usize) -> Result<(Event, bool), IntcodeError> {
+    let event = self.program.system.send_input();
+    let changed = match event {
+      Event::Input(i) | Event::BreakOnInput(i) => self.store(destination, i),
+      Event::EndOfInput => return Err(IntcodeError::InputExhausted { address: at }),
+      _ => false,
+    };
+    Ok((event, changed))
+  }
+  /// Where to leave the program after the system answered an IO instruction with `event`, or
+  /// `None` to carry on with the block.
+  fn stop(&mut self, at: usize, next: usize, event: Event, changed: bool) -> Option<Option<Event>> {
+    self.program.position = match event {
+      Event::Input(_) | Event::Output(_) if !changed => return None,
+      Event::AskForInputAgain | Event::Halted => at,
+      _ => next,
+    };
+    Some(Some(event))
+  }
+}
+
+fn is_code(address: usize) -> bool {
+  CODE
+    .binary_search_by(|&(start, end)| {
+      if end <= address {
+        std::cmp::Ordering::Less
+      } else if start > address {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    })
+    .is_ok()
+}
+
+/// The image the translation was made from.
+const IMAGE: &[I] = &[109, 424, 203, 1, 21102, 11, 1, 0, 1106, 0, 282, 21101, 0, 18, 0, 1106, 0, 259, 1201, 1, 0, 221, 203, 1, 21102, 1, 31, 0, 1106, 0, 282, 21101, 0, 38, 0, 1106, 0, 259, 20102, 1, 23, 2, 21202, 1, 1, 3, 21101, 1, 0, 1, 21101, 0, 57, 0, 1105, 1, 303, 2101, 0, 1, 222, 20101, 0, 221, 3, 21001, 221, 0, 2, 21102, 1, 259, 1, 21101, 0, 80, 0, 1105, 1, 225, 21101, 185, 0, 2, 21102, 91, 1, 0, 1106, 0, 303, 1202, 1, 1, 223, 21001, 222, 0, 4, 21102, 259, 1, 3, 21101, 225, 0, 2, 21102, 1, 225, 1, 21101, 0, 118, 0, 1106, 0, 225, 20102, 1, 222, 3, 21102, 1, 131, 2, 21101, 133, 0, 0, 1106, 0, 303, 21202, 1, -1, 1, 22001, 223, 1, 1, 21101, 148, 0, 0, 1105, 1, 259, 2101, 0, 1, 223, 21002, 221, 1, 4, 21002, 222, 1, 3, 21101, 0, 16, 2, 1001, 132, -2, 224, 1002, 224, 2, 224, 1001, 224, 3, 224, 1002, 132, -1, 132, 1, 224, 132, 224, 21001, 224, 1, 1, 21101, 0, 195, 0, 106, 0, 109, 20207, 1, 223, 2, 20101, 0, 23, 1, 21102, 1, -1, 3, 21101, 0, 214, 0, 1105, 1, 303, 22101, 1, 1, 1, 204, 1, 99, 0, 0, 0, 0, 109, 5, 1201, -4, 0, 249, 22101, 0, -3, 1, 22101, 0, -2, 2, 21201, -1, 0, 3, 21101, 0, 250, 0, 1106, 0, 225, 21201, 1, 0, -4, 109, -5, 2106, 0, 0, 109, 3, 22107, 0, -2, -1, 21202, -1, 2, -1, 21201, -1, -1, -1, 22202, -1, -2, -2, 109, -3, 2106, 0, 0, 109, 3, 21207, -2, 0, -1, 1206, -1, 294, 104, 0, 99, 22102, 1, -2, -2, 109, -3, 2105, 1, 0, 109, 5, 22207, -3, -4, -1, 1206, -1, 346, 22201, -4, -3, -4, 21202, -3, -1, -1, 22201, -4, -1, 2, 21202, 2, -1, -1, 22201, -4, -1, 1, 21201, -2, 0, 3, 21101, 343, 0, 0, 1106, 0, 303, 1105, 1, 415, 22207, -2, -3, -1, 1206, -1, 387, 22201, -3, -2, -3, 21202, -2, -1, -1, 22201, -3, -1, 3, 21202, 3, -1, -1, 22201, -3, -1, 2, 22101, 0, -4, 1, 21102, 384, 1, 0, 1106, 0, 303, 1105, 1, 415, 21202, -4, -1, -4, 22201, -4, -3, -4, 22202, -3, -2, -2, 22202, -2, -4, -4, 22202, -3, -2, -3, 21202, -4, -1, -2, 22201, -3, -2, 1, 21201, 1, 0, -4, 109, -5, 2106, 0, 0];
+/// Words built into the translation, as `(start, end)`.
+const CODE: &[(usize, usize)] = &[(0, 132), (133, 221), (225, 249), (250, 424)];
+/// Each translated block, as `(start, end)`.
+const BLOCKS: &[(usize, usize)] = &[(0, 11), (11, 18), (18, 31), (31, 38), (38, 57), (57, 80), (80, 91), (91, 118), (118, 133), (133, 148), (148, 195), (195, 214), (214, 221), (225, 250), (250, 259), (259, 282), (282, 291), (291, 294), (294, 303), (303, 312), (312, 343), (343, 346), (346, 353), (353, 384), (384, 387), (387, 415), (415, 424)];
+
+impl<S: System<Word = I>> Compiled<S> {
+  /// Runs translated blocks from the current position until something stops them.
+  fn block(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let mut pc = self.program.position;
+    loop {
+      pc = match pc {
+        0 if !self.stale[0] => {
+          // 0: arb #424
+          self.program.relative_position = self.program.relative_position.wrapping_add(424);
+          // 2: in rb+1
+          let destination = self.relative(2, 1)?;
+          let (event, changed) = self.input(2, destination)?;
+          if let Some(stop) = self.stop(2, 4, event, changed) { return Ok(stop); }
+          // 4: mul #11, #1, rb+0
+          let value = I::wrapping_mul(11, 1);
+          if self.store(self.relative(4, 0)?, value) { self.program.position = 8; return Ok(None); }
+          // 8: jf #0, #282
+          282
+        }
+        11 if !self.stale[1] => {
+          // 11: add #0, #18, rb+0
+          let value = I::wrapping_add(0, 18);
+          if self.store(self.relative(11, 0)?, value) { self.program.position = 15; return Ok(None); }
+          // 15: jf #0, #259
+          259
+        }
+        18 if !self.stale[2] => {
+          // 18: add rb+1, #0, [221]
+          self.set(221, I::wrapping_add(self.program.codes.get(self.relative(18, 1)?), 0));
+          // 22: in rb+1
+          let destination = self.relative(22, 1)?;
+          let (event, changed) = self.input(22, destination)?;
+          if let Some(stop) = self.stop(22, 24, event, changed) { return Ok(stop); }
+          // 24: mul #1, #31, rb+0
+          let value = I::wrapping_mul(1, 31);
+          if self.store(self.relative(24, 0)?, value) { self.program.position = 28; return Ok(None); }
+          // 28: jf #0, #282
+          282
+        }
+        31 if !self.stale[3] => {
+          // 31: add #0, #38, rb+0
+          let value = I::wrapping_add(0, 38);
+          if self.store(self.relative(31, 0)?, value) { self.program.position = 35; return Ok(None); }
+          // 35: jf #0, #259
+          259
+        }
+        38 if !self.stale[4] => {
+          // 38: mul #1, [23], rb+2
+          let value = I::wrapping_mul(1, self.program.codes.get(23));
+          if self.store(self.relative(38, 2)?, value) { self.program.position = 42; return Ok(None); }
+          // 42: mul rb+1, #1, rb+3
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(42, 1)?), 1);
+          if self.store(self.relative(42, 3)?, value) { self.program.position = 46; return Ok(None); }
+          // 46: add #1, #0, rb+1
+          let value = I::wrapping_add(1, 0);
+          if self.store(self.relative(46, 1)?, value) { self.program.position = 50; return Ok(None); }
+          // 50: add #0, #57, rb+0
+          let value = I::wrapping_add(0, 57);
+          if self.store(self.relative(50, 0)?, value) { self.program.position = 54; return Ok(None); }
+          // 54: jt #1, #303
+          303
+        }
+        57 if !self.stale[5] => {
+          // 57: add #0, rb+1, [222]
+          self.set(222, I::wrapping_add(0, self.program.codes.get(self.relative(57, 1)?)));
+          // 61: add #0, [221], rb+3
+          let value = I::wrapping_add(0, self.program.codes.get(221));
+          if self.store(self.relative(61, 3)?, value) { self.program.position = 65; return Ok(None); }
+          // 65: add [221], #0, rb+2
+          let value = I::wrapping_add(self.program.codes.get(221), 0);
+          if self.store(self.relative(65, 2)?, value) { self.program.position = 69; return Ok(None); }
+          // 69: mul #1, #259, rb+1
+          let value = I::wrapping_mul(1, 259);
+          if self.store(self.relative(69, 1)?, value) { self.program.position = 73; return Ok(None); }
+          // 73: add #0, #80, rb+0
+          let value = I::wrapping_add(0, 80);
+          if self.store(self.relative(73, 0)?, value) { self.program.position = 77; return Ok(None); }
+          // 77: jt #1, #225
+          225
+        }
+        80 if !self.stale[6] => {
+          // 80: add #185, #0, rb+2
+          let value = I::wrapping_add(185, 0);
+          if self.store(self.relative(80, 2)?, value) { self.program.position = 84; return Ok(None); }
+          // 84: mul #91, #1, rb+0
+          let value = I::wrapping_mul(91, 1);
+          if self.store(self.relative(84, 0)?, value) { self.program.position = 88; return Ok(None); }
+          // 88: jf #0, #303
+          303
+        }
+        91 if !self.stale[7] => {
+          // 91: mul rb+1, #1, [223]
+          self.set(223, I::wrapping_mul(self.program.codes.get(self.relative(91, 1)?), 1));
+          // 95: add [222], #0, rb+4
+          let value = I::wrapping_add(self.program.codes.get(222), 0);
+          if self.store(self.relative(95, 4)?, value) { self.program.position = 99; return Ok(None); }
+          // 99: mul #259, #1, rb+3
+          let value = I::wrapping_mul(259, 1);
+          if self.store(self.relative(99, 3)?, value) { self.program.position = 103; return Ok(None); }
+          // 103: add #225, #0, rb+2
+          let value = I::wrapping_add(225, 0);
+          if self.store(self.relative(103, 2)?, value) { self.program.position = 107; return Ok(None); }
+          // 107: mul #1, #225, rb+1
+          let value = I::wrapping_mul(1, 225);
+          if self.store(self.relative(107, 1)?, value) { self.program.position = 111; return Ok(None); }
+          // 111: add #0, #118, rb+0
+          let value = I::wrapping_add(0, 118);
+          if self.store(self.relative(111, 0)?, value) { self.program.position = 115; return Ok(None); }
+          // 115: jf #0, #225
+          225
+        }
+        118 if !self.stale[8] => {
+          // 118: mul #1, [222], rb+3
+          let value = I::wrapping_mul(1, self.program.codes.get(222));
+          if self.store(self.relative(118, 3)?, value) { self.program.position = 122; return Ok(None); }
+          // 122: mul #1, #131, rb+2
+          let value = I::wrapping_mul(1, 131);
+          if self.store(self.relative(122, 2)?, value) { self.program.position = 126; return Ok(None); }
+          // 126: add #133, #0, rb+0
+          let value = I::wrapping_add(133, 0);
+          if self.store(self.relative(126, 0)?, value) { self.program.position = 130; return Ok(None); }
+          // 130: jf #0, #303
+          self.address(130, self.program.codes.get(132))?
+        }
+        133 if !self.stale[9] => {
+          // 133: mul rb+1, #-1, rb+1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(133, 1)?), -1);
+          if self.store(self.relative(133, 1)?, value) { self.program.position = 137; return Ok(None); }
+          // 137: add [223], rb+1, rb+1
+          let value = I::wrapping_add(self.program.codes.get(223), self.program.codes.get(self.relative(137, 1)?));
+          if self.store(self.relative(137, 1)?, value) { self.program.position = 141; return Ok(None); }
+          // 141: add #148, #0, rb+0
+          let value = I::wrapping_add(148, 0);
+          if self.store(self.relative(141, 0)?, value) { self.program.position = 145; return Ok(None); }
+          // 145: jt #1, #259
+          259
+        }
+        148 if !self.stale[10] => {
+          // 148: add #0, rb+1, [223]
+          self.set(223, I::wrapping_add(0, self.program.codes.get(self.relative(148, 1)?)));
+          // 152: mul [221], #1, rb+4
+          let value = I::wrapping_mul(self.program.codes.get(221), 1);
+          if self.store(self.relative(152, 4)?, value) { self.program.position = 156; return Ok(None); }
+          // 156: mul [222], #1, rb+3
+          let value = I::wrapping_mul(self.program.codes.get(222), 1);
+          if self.store(self.relative(156, 3)?, value) { self.program.position = 160; return Ok(None); }
+          // 160: add #0, #16, rb+2
+          let value = I::wrapping_add(0, 16);
+          if self.store(self.relative(160, 2)?, value) { self.program.position = 164; return Ok(None); }
+          // 164: add [132], #-2, [224]
+          self.set(224, I::wrapping_add(self.program.codes.get(132), -2));
+          // 168: mul [224], #2, [224]
+          self.set(224, I::wrapping_mul(self.program.codes.get(224), 2));
+          // 172: add [224], #3, [224]
+          self.set(224, I::wrapping_add(self.program.codes.get(224), 3));
+          // 176: mul [132], #-1, [132]
+          self.set(132, I::wrapping_mul(self.program.codes.get(132), -1));
+          // 180: add [224], [132], [224]
+          self.set(224, I::wrapping_add(self.program.codes.get(224), self.program.codes.get(132)));
+          // 184: add [224], #1, rb+1
+          let value = I::wrapping_add(self.program.codes.get(224), 1);
+          if self.store(self.relative(184, 1)?, value) { self.program.position = 188; return Ok(None); }
+          // 188: add #0, #195, rb+0
+          let value = I::wrapping_add(0, 195);
+          if self.store(self.relative(188, 0)?, value) { self.program.position = 192; return Ok(None); }
+          // 192: jf #0, [109]
+          self.address(192, self.program.codes.get(109))?
+        }
+        195 if !self.stale[11] => {
+          // 195: lt rb+1, [223], rb+2
+          let value = (self.program.codes.get(self.relative(195, 1)?) < self.program.codes.get(223)) as I;
+          if self.store(self.relative(195, 2)?, value) { self.program.position = 199; return Ok(None); }
+          // 199: add #0, [23], rb+1
+          let value = I::wrapping_add(0, self.program.codes.get(23));
+          if self.store(self.relative(199, 1)?, value) { self.program.position = 203; return Ok(None); }
+          // 203: mul #1, #-1, rb+3
+          let value = I::wrapping_mul(1, -1);
+          if self.store(self.relative(203, 3)?, value) { self.program.position = 207; return Ok(None); }
+          // 207: add #0, #214, rb+0
+          let value = I::wrapping_add(0, 214);
+          if self.store(self.relative(207, 0)?, value) { self.program.position = 211; return Ok(None); }
+          // 211: jt #1, #303
+          303
+        }
+        214 if !self.stale[12] => {
+          // 214: add #1, rb+1, rb+1
+          let value = I::wrapping_add(1, self.program.codes.get(self.relative(214, 1)?));
+          if self.store(self.relative(214, 1)?, value) { self.program.position = 218; return Ok(None); }
+          // 218: out rb+1
+          let event = self.program.system.take_output(self.program.codes.get(self.relative(218, 1)?));
+          if let Some(stop) = self.stop(218, 220, event, false) { return Ok(stop); }
+          // 220: hlt
+          self.program.position = 220;
+          return Ok(Some(Event::Halted));
+        }
+        225 if !self.stale[13] => {
+          // 225: arb #5
+          self.program.relative_position = self.program.relative_position.wrapping_add(5);
+          // 227: add rb-4, #0, [249]
+          self.set(249, I::wrapping_add(self.program.codes.get(self.relative(227, -4)?), 0));
+          // 231: add #0, rb-3, rb+1
+          let value = I::wrapping_add(0, self.program.codes.get(self.relative(231, -3)?));
+          if self.store(self.relative(231, 1)?, value) { self.program.position = 235; return Ok(None); }
+          // 235: add #0, rb-2, rb+2
+          let value = I::wrapping_add(0, self.program.codes.get(self.relative(235, -2)?));
+          if self.store(self.relative(235, 2)?, value) { self.program.position = 239; return Ok(None); }
+          // 239: add rb-1, #0, rb+3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(239, -1)?), 0);
+          if self.store(self.relative(239, 3)?, value) { self.program.position = 243; return Ok(None); }
+          // 243: add #0, #250, rb+0
+          let value = I::wrapping_add(0, 250);
+          if self.store(self.relative(243, 0)?, value) { self.program.position = 247; return Ok(None); }
+          // 247: jf #0, #225
+          self.address(247, self.program.codes.get(249))?
+        }
+        250 if !self.stale[14] => {
+          // 250: add rb+1, #0, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(250, 1)?), 0);
+          if self.store(self.relative(250, -4)?, value) { self.program.position = 254; return Ok(None); }
+          // 254: arb #-5
+          self.program.relative_position = self.program.relative_position.wrapping_add(-5);
+          // 256: jf #0, rb+0
+          self.address(256, self.program.codes.get(self.relative(256, 0)?))?
+        }
+        259 if !self.stale[15] => {
+          // 259: arb #3
+          self.program.relative_position = self.program.relative_position.wrapping_add(3);
+          // 261: lt #0, rb-2, rb-1
+          let value = (0 < self.program.codes.get(self.relative(261, -2)?)) as I;
+          if self.store(self.relative(261, -1)?, value) { self.program.position = 265; return Ok(None); }
+          // 265: mul rb-1, #2, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(265, -1)?), 2);
+          if self.store(self.relative(265, -1)?, value) { self.program.position = 269; return Ok(None); }
+          // 269: add rb-1, #-1, rb-1
+          let value = I::wrapping_add(self.program.codes.get(self.relative(269, -1)?), -1);
+          if self.store(self.relative(269, -1)?, value) { self.program.position = 273; return Ok(None); }
+          // 273: mul rb-1, rb-2, rb-2
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(273, -1)?), self.program.codes.get(self.relative(273, -2)?));
+          if self.store(self.relative(273, -2)?, value) { self.program.position = 277; return Ok(None); }
+          // 277: arb #-3
+          self.program.relative_position = self.program.relative_position.wrapping_add(-3);
+          // 279: jf #0, rb+0
+          self.address(279, self.program.codes.get(self.relative(279, 0)?))?
+        }
+        282 if !self.stale[16] => {
+          // 282: arb #3
+          self.program.relative_position = self.program.relative_position.wrapping_add(3);
+          // 284: lt rb-2, #0, rb-1
+          let value = (self.program.codes.get(self.relative(284, -2)?) < 0) as I;
+          if self.store(self.relative(284, -1)?, value) { self.program.position = 288; return Ok(None); }
+          // 288: jf rb-1, #294
+          if self.program.codes.get(self.relative(288, -1)?) == 0 { 294 } else { 291 }
+        }
+        291 if !self.stale[17] => {
+          // 291: out #0
+          let event = self.program.system.take_output(0);
+          if let Some(stop) = self.stop(291, 293, event, false) { return Ok(stop); }
+          // 293: hlt
+          self.program.position = 293;
+          return Ok(Some(Event::Halted));
+        }
+        294 if !self.stale[18] => {
+          // 294: mul #1, rb-2, rb-2
+          let value = I::wrapping_mul(1, self.program.codes.get(self.relative(294, -2)?));
+          if self.store(self.relative(294, -2)?, value) { self.program.position = 298; return Ok(None); }
+          // 298: arb #-3
+          self.program.relative_position = self.program.relative_position.wrapping_add(-3);
+          // 300: jt #1, rb+0
+          self.address(300, self.program.codes.get(self.relative(300, 0)?))?
+        }
+        303 if !self.stale[19] => {
+          // 303: arb #5
+          self.program.relative_position = self.program.relative_position.wrapping_add(5);
+          // 305: lt rb-3, rb-4, rb-1
+          let value = (self.program.codes.get(self.relative(305, -3)?) < self.program.codes.get(self.relative(305, -4)?)) as I;
+          if self.store(self.relative(305, -1)?, value) { self.program.position = 309; return Ok(None); }
+          // 309: jf rb-1, #346
+          if self.program.codes.get(self.relative(309, -1)?) == 0 { 346 } else { 312 }
+        }
+        312 if !self.stale[20] => {
+          // 312: add rb-4, rb-3, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(312, -4)?), self.program.codes.get(self.relative(312, -3)?));
+          if self.store(self.relative(312, -4)?, value) { self.program.position = 316; return Ok(None); }
+          // 316: mul rb-3, #-1, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(316, -3)?), -1);
+          if self.store(self.relative(316, -1)?, value) { self.program.position = 320; return Ok(None); }
+          // 320: add rb-4, rb-1, rb+2
+          let value = I::wrapping_add(self.program.codes.get(self.relative(320, -4)?), self.program.codes.get(self.relative(320, -1)?));
+          if self.store(self.relative(320, 2)?, value) { self.program.position = 324; return Ok(None); }
+          // 324: mul rb+2, #-1, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(324, 2)?), -1);
+          if self.store(self.relative(324, -1)?, value) { self.program.position = 328; return Ok(None); }
+          // 328: add rb-4, rb-1, rb+1
+          let value = I::wrapping_add(self.program.codes.get(self.relative(328, -4)?), self.program.codes.get(self.relative(328, -1)?));
+          if self.store(self.relative(328, 1)?, value) { self.program.position = 332; return Ok(None); }
+          // 332: add rb-2, #0, rb+3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(332, -2)?), 0);
+          if self.store(self.relative(332, 3)?, value) { self.program.position = 336; return Ok(None); }
+          // 336: add #343, #0, rb+0
+          let value = I::wrapping_add(343, 0);
+          if self.store(self.relative(336, 0)?, value) { self.program.position = 340; return Ok(None); }
+          // 340: jf #0, #303
+          303
+        }
+        343 if !self.stale[21] => {
+          // 343: jt #1, #415
+          415
+        }
+        346 if !self.stale[22] => {
+          // 346: lt rb-2, rb-3, rb-1
+          let value = (self.program.codes.get(self.relative(346, -2)?) < self.program.codes.get(self.relative(346, -3)?)) as I;
+          if self.store(self.relative(346, -1)?, value) { self.program.position = 350; return Ok(None); }
+          // 350: jf rb-1, #387
+          if self.program.codes.get(self.relative(350, -1)?) == 0 { 387 } else { 353 }
+        }
+        353 if !self.stale[23] => {
+          // 353: add rb-3, rb-2, rb-3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(353, -3)?), self.program.codes.get(self.relative(353, -2)?));
+          if self.store(self.relative(353, -3)?, value) { self.program.position = 357; return Ok(None); }
+          // 357: mul rb-2, #-1, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(357, -2)?), -1);
+          if self.store(self.relative(357, -1)?, value) { self.program.position = 361; return Ok(None); }
+          // 361: add rb-3, rb-1, rb+3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(361, -3)?), self.program.codes.get(self.relative(361, -1)?));
+          if self.store(self.relative(361, 3)?, value) { self.program.position = 365; return Ok(None); }
+          // 365: mul rb+3, #-1, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(365, 3)?), -1);
+          if self.store(self.relative(365, -1)?, value) { self.program.position = 369; return Ok(None); }
+          // 369: add rb-3, rb-1, rb+2
+          let value = I::wrapping_add(self.program.codes.get(self.relative(369, -3)?), self.program.codes.get(self.relative(369, -1)?));
+          if self.store(self.relative(369, 2)?, value) { self.program.position = 373; return Ok(None); }
+          // 373: add #0, rb-4, rb+1
+          let value = I::wrapping_add(0, self.program.codes.get(self.relative(373, -4)?));
+          if self.store(self.relative(373, 1)?, value) { self.program.position = 377; return Ok(None); }
+          // 377: mul #384, #1, rb+0
+          let value = I::wrapping_mul(384, 1);
+          if self.store(self.relative(377, 0)?, value) { self.program.position = 381; return Ok(None); }
+          // 381: jf #0, #303
+          303
+        }
+        384 if !self.stale[24] => {
+          // 384: jt #1, #415
+          415
+        }
+        387 if !self.stale[25] => {
+          // 387: mul rb-4, #-1, rb-4
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(387, -4)?), -1);
+          if self.store(self.relative(387, -4)?, value) { self.program.position = 391; return Ok(None); }
+          // 391: add rb-4, rb-3, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(391, -4)?), self.program.codes.get(self.relative(391, -3)?));
+          if self.store(self.relative(391, -4)?, value) { self.program.position = 395; return Ok(None); }
+          // 395: mul rb-3, rb-2, rb-2
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(395, -3)?), self.program.codes.get(self.relative(395, -2)?));
+          if self.store(self.relative(395, -2)?, value) { self.program.position = 399; return Ok(None); }
+          // 399: mul rb-2, rb-4, rb-4
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(399, -2)?), self.program.codes.get(self.relative(399, -4)?));
+          if self.store(self.relative(399, -4)?, value) { self.program.position = 403; return Ok(None); }
+          // 403: mul rb-3, rb-2, rb-3
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(403, -3)?), self.program.codes.get(self.relative(403, -2)?));
+          if self.store(self.relative(403, -3)?, value) { self.program.position = 407; return Ok(None); }
+          // 407: mul rb-4, #-1, rb-2
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(407, -4)?), -1);
+          if self.store(self.relative(407, -2)?, value) { self.program.position = 411; return Ok(None); }
+          // 411: add rb-3, rb-2, rb+1
+          let value = I::wrapping_add(self.program.codes.get(self.relative(411, -3)?), self.program.codes.get(self.relative(411, -2)?));
+          if self.store(self.relative(411, 1)?, value) { self.program.position = 415; return Ok(None); }
+          415
+        }
+        415 if !self.stale[26] => {
+          // 415: add rb+1, #0, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(415, 1)?), 0);
+          if self.store(self.relative(415, -4)?, value) { self.program.position = 419; return Ok(None); }
+          // 419: arb #-5
+          self.program.relative_position = self.program.relative_position.wrapping_add(-5);
+          // 421: jf #0, rb+0
+          self.address(421, self.program.codes.get(self.relative(421, 0)?))?
+        }
+        _ => {
+          self.program.position = pc;
+          return self.interpret();
+        }
+      };
+    }
+  }
+}
@@ -0,0 +1,492 @@
+//! Translated from Intcode by `intcode aot`. Do not edit.
+#![allow(dead_code, clippy::all)]
+use adventofcode2019::intcode::{Event, IntcodeError, Program, System, I};
+
+/// Runs a `Program` through the translated blocks, handing any address that was not
+/// translated to the interpreter one instruction at a time.
+///
+/// Blocks whose code has been overwritten are interpreted from then on, apart from operands
+/// the program itself patches, which are read from memory. Everything is interpreted when
+/// checked arithmetic, limits, profiling or coverage are on, since the translation implements
+/// none of them.
+pub struct Compiled<S: System<Word = I>> {
+  pub program: Program<S>,
+  interpreted: bool,
+  stale: Vec<bool>,
+}
+
+impl<S: System<Word = I>> Compiled<S> {
+  pub fn new(program: Program<S>) -> Self {
+    let mut compiled = Self {
+      program,
+      interpreted: false,
+      stale: vec![],
+    };
+    compiled.check();
+    compiled
+  }
+  pub fn reset(&mut self) {
+    self.program.reset();
+    self.check();
+  }
+  /// Same as `Program::run`.
+  pub fn run(&mut self) -> Result<Option<I>, IntcodeError> {
+    loop {
+      match self.run_to_event()? {
+        Event::Halted | Event::AskForInputAgain => break,
+        _ => {}
+      }
+    }
+    let output = self.program.system.end();
+    if self.program.reset_after_run {
+      self.reset();
+    }
+    Ok(output)
+  }
+  /// Same as `Program::run_to_event`.
+  pub fn run_to_event(&mut self) -> Result<Event, IntcodeError> {
+    loop {
+      let event = if self.interpreted {
+        self.interpret()
+      } else {
+        self.block().map_err(|e| {
+          self.program.position = e.address();
+          e
+        })
+      };
+      match event? {
+        Some(Event::Input(_)) | Some(Event::Output(_)) | None => {}
+        Some(e) => return Ok(e),
+      }
+    }
+  }
+  fn check(&mut self) {
+    let program = &self.program;
+    self.interpreted = program.checked
+      || program.limits.is_some()
+      || program.profile.is_some()
+      || program.coverage.is_some();
+    self.stale = BLOCKS
+      .iter()
+      .map(|&(start, end)| (start..end).any(|a| is_code(a) && program.codes.get(a) != IMAGE[a]))
+      .collect();
+  }
+  /// Executes one instruction in the interpreter, noticing if it overwrites code.
+  fn interpret(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let written = self
+      .program
+      .opcode()
+      .ok()
+      .and_then(|opcode| opcode.destination())
+      .and_then(|p| self.program.destination(&p).ok())
+      .filter(|address| is_code(*address));
+    let old = written.map(|address| self.program.codes.get(address));
+    let event = self.program.step()?;
+    if let (Some(address), Some(old)) = (written, old) {
+      if self.program.codes.get(address) != old {
+        self.invalidate(address);
+      }
+    }
+    Ok(event)
+  }
+  fn invalidate(&mut self, address: usize) {
+    for (n, &(start, end)) in BLOCKS.iter().enumerate() {
+      if start <= address && address < end {
+        self.stale[n] = true;
+      }
+    }
+  }
+  fn relative(&self, at: usize, offset: I) -> Result<usize, IntcodeError> {
+    self.address(at, self.program.relative_position.wrapping_add(offset))
+  }
+  fn address(&self, at: usize, target: I) -> Result<usize, IntcodeError> {
+    if target < 0 {
+      return Err(IntcodeError::NegativeAddress {
+        address: at,
+        target,
+      });
+    }
+    Ok(target as usize)
+  }
+  /// Writes to an address the translation knows is not code.
+  fn set(&mut self, address: usize, value: I) {
+    self.program.codes.set(address, value);
+    if let Some(cache) = &mut self.program.cache {
+      cache.invalidate(address);
+    }
+  }
+  /// Writes anywhere, returning whether translated code changed.
+  fn store(&mut self, address: usize, value: I) -> bool {
+    let changed = is_code(address) && self.program.codes.get(address) != value;
+    if changed {
+      self.invalidate(address);
+    }
+    self.set(address, value);
+    changed
+  }
+  fn input(&mut self, at: usize, destination: usize) -> Result<(Event, bool), IntcodeError> {
+    let event = self.program.system.send_input();
+    let changed = match event {
+      Event::Input(i) | Event::BreakOnInput(i) => self.store(destination, i),
+      Event::EndOfInput => return Err(IntcodeError::InputExhausted { address: at }),
+      _ => false,
+    };
+    Ok((event, changed))
+  }
+  /// Where to leave the program after the system answered an IO instruction with `event`, or
+  /// `None` to carry on with the block.
+  fn stop(&mut self, at: usize, next: usize, event: Event, changed: bool) -> Option<Option<Event>> {
+    self.program.position = match event {
+      Event::Input(_) | Event::Output(_) if !changed => return None,
+      Event::AskForInputAgain | Event::Halted => at,
+      _ => next,
+    };
+    Some(Some(event))
+  }
+}
+
+fn is_code(address: usize) -> bool {
+  CODE
+    .binary_search_by(|&(start, end)| {
+      if end <= address {
+        std::cmp::Ordering::Less
+      } else if start > address {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    })
+    .is_ok()
+}
+
+/// The image the translation was made from.
+const IMAGE: &[I] = &[3, 62, 1001, 62, 11, 10, 109, 2219, 105, 1, 0, 1916, 2159, 891, 1246, 1623, 794, 1724, 1380, 1312, 965, 1025, 1056, 1279, 1691, 631, 1759, 2027, 730, 1792, 1415, 1510, 1151, 1555, 1211, 1089, 1823, 932, 1481, 1588, 763, 1658, 1957, 1120, 829, 1994, 699, 994, 666, 1182, 1452, 2188, 2056, 602, 1852, 860, 571, 1347, 2097, 1883, 2128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 64, 1008, 64, -1, 62, 1006, 62, 88, 1006, 61, 170, 1105, 1, 73, 3, 65, 20102, 1, 64, 1, 21001, 66, 0, 2, 21101, 0, 105, 0, 1105, 1, 436, 1201, 1, -1, 64, 1007, 64, 0, 62, 1005, 62, 73, 7, 64, 67, 62, 1006, 62, 73, 1002, 64, 2, 133, 1, 133, 68, 133, 101, 0, 0, 62, 1001, 133, 1, 140, 8, 0, 65, 63, 2, 63, 62, 62, 1005, 62, 73, 1002, 64, 2, 161, 1, 161, 68, 161, 1102, 1, 1, 0, 1001, 161, 1, 169, 1002, 65, 1, 0, 1101, 1, 0, 61, 1101, 0, 0, 63, 7, 63, 67, 62, 1006, 62, 203, 1002, 63, 2, 194, 1, 68, 194, 194, 1006, 0, 73, 1001, 63, 1, 63, 1105, 1, 178, 21101, 0, 210, 0, 106, 0, 69, 1201, 1, 0, 70, 1102, 1, 0, 63, 7, 63, 71, 62, 1006, 62, 250, 1002, 63, 2, 234, 1, 72, 234, 234, 4, 0, 101, 1, 234, 240, 4, 0, 4, 70, 1001, 63, 1, 63, 1105, 1, 218, 1105, 1, 73, 109, 4, 21102, 0, 1, -3, 21101, 0, 0, -2, 20207, -2, 67, -1, 1206, -1, 293, 1202, -2, 2, 283, 101, 1, 283, 283, 1, 68, 283, 283, 22001, 0, -3, -3, 21201, -2, 1, -2, 1106, 0, 263, 22101, 0, -3, -3, 109, -4, 2105, 1, 0, 109, 4, 21101, 1, 0, -3, 21102, 0, 1, -2, 20207, -2, 67, -1, 1206, -1, 342, 1202, -2, 2, 332, 101, 1, 332, 332, 1, 68, 332, 332, 22002, 0, -3, -3, 21201, -2, 1, -2, 1105, 1, 312, 21201, -3, 0, -3, 109, -4, 2105, 1, 0, 109, 1, 101, 1, 68, 359, 20102, 1, 0, 1, 101, 3, 68, 366, 21002, 0, 1, 2, 21101, 0, 376, 0, 1105, 1, 436, 22101, 0, 1, 0, 109, -1, 2105, 1, 0, 1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384, 32768, 65536, 131072, 262144, 524288, 1048576, 2097152, 4194304, 8388608, 16777216, 33554432, 67108864, 134217728, 268435456, 536870912, 1073741824, 2147483648, 4294967296, 8589934592, 17179869184, 34359738368, 68719476736, 137438953472, 274877906944, 549755813888, 1099511627776, 2199023255552, 4398046511104, 8796093022208, 17592186044416, 35184372088832, 70368744177664, 140737488355328, 281474976710656, 562949953421312, 1125899906842624, 109, 8, 21202, -6, 10, -5, 22207, -7, -5, -5, 1205, -5, 521, 21102, 0, 1, -4, 21101, 0, 0, -3, 21101, 0, 51, -2, 21201, -2, -1, -2, 1201, -2, 385, 470, 21002, 0, 1, -1, 21202, -3, 2, -3, 22207, -7, -1, -5, 1205, -5, 496, 21201, -3, 1, -3, 22102, -1, -1, -5, 22201, -7, -5, -7, 22207, -3, -6, -5, 1205, -5, 515, 22102, -1, -6, -5, 22201, -3, -5, -3, 22201, -1, -4, -4, 1205, -2, 461, 1105, 1, 547, 21102, 1, -1, -4, 21202, -6, -1, -6, 21207, -7, 0, -5, 1205, -5, 547, 22201, -7, -6, -7, 21201, -4, 1, -4, 1106, 0, 529, 22102, 1, -4, -7, 109, -8, 2106, 0, 0, 109, 1, 101, 1, 68, 564, 20101, 0, 0, 0, 109, -1, 2105, 1, 0, 1102, 1, 151, 66, 1101, 1, 0, 67, 1101, 0, 598, 68, 1101, 0, 556, 69, 1102, 1, 1, 71, 1101, 600, 0, 72, 1106, 0, 73, 1, 3, 17, 29671, 1101, 0, 52981, 66, 1101, 1, 0, 67, 1101, 629, 0, 68, 1102, 556, 1, 69, 1102, 1, 0, 71, 1102, 1, 631, 72, 1106, 0, 73, 1, 1639, 1101, 0, 97771, 66, 1101, 0, 3, 67, 1101, 658, 0, 68, 1101, 253, 0, 69, 1101, 0, 1, 71, 1102, 1, 664, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 46, 54377, 1101, 0, 27091, 66, 1101, 1, 0, 67, 1101, 693, 0, 68, 1102, 1, 556, 69, 1101, 0, 2, 71, 1101, 695, 0, 72, 1106, 0, 73, 1, 2, 2, 93305, 2, 111966, 1102, 64879, 1, 66, 1101, 0, 1, 67, 1101, 0, 726, 68, 1102, 1, 556, 69, 1102, 1, 1, 71, 1101, 0, 728, 72, 1105, 1, 73, 1, 157651, 41, 1877, 1102, 1, 29671, 66, 1102, 2, 1, 67, 1101, 757, 0, 68, 1101, 302, 0, 69, 1102, 1, 1, 71, 1102, 1, 761, 72, 1106, 0, 73, 0, 0, 0, 0, 14, 97771, 1101, 0, 25951, 66, 1102, 1, 1, 67, 1101, 0, 790, 68, 1101, 0, 556, 69, 1101, 1, 0, 71, 1101, 0, 792, 72, 1106, 0, 73, 1, 35246, 41, 5631, 1102, 1, 50087, 66, 1102, 3, 1, 67, 1102, 1, 821, 68, 1102, 1, 302, 69, 1101, 0, 1, 71, 1101, 827, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 23, 200986, 1102, 1, 39079, 66, 1101, 1, 0, 67, 1101, 0, 856, 68, 1102, 556, 1, 69, 1101, 0, 1, 71, 1102, 1, 858, 72, 1106, 0, 73, 1, -17, 4, 10253, 1102, 7951, 1, 66, 1101, 0, 1, 67, 1102, 887, 1, 68, 1101, 556, 0, 69, 1101, 1, 0, 71, 1101, 0, 889, 72, 1105, 1, 73, 1, -42282, 41, 7508, 1102, 1, 18661, 66, 1101, 0, 6, 67, 1101, 918, 0, 68, 1102, 1, 302, 69, 1102, 1, 1, 71, 1102, 1, 930, 72, 1106, 0, 73, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 34, 35318, 1102, 1, 85091, 66, 1102, 2, 1, 67, 1102, 1, 959, 68, 1102, 302, 1, 69, 1101, 1, 0, 71, 1101, 963, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 12, 9187, 1101, 6151, 0, 66, 1102, 1, 1, 67, 1102, 992, 1, 68, 1101, 0, 556, 69, 1102, 1, 0, 71, 1102, 1, 994, 72, 1105, 1, 73, 1, 1375, 1102, 1, 88379, 66, 1101, 1, 0, 67, 1101, 0, 1021, 68, 1101, 556, 0, 69, 1101, 1, 0, 71, 1101, 0, 1023, 72, 1105, 1, 73, 1, 4397, 4, 30759, 1102, 101653, 1, 66, 1101, 0, 1, 67, 1102, 1, 1052, 68, 1102, 556, 1, 69, 1101, 0, 1, 71, 1101, 1054, 0, 72, 1105, 1, 73, 1, 63072, 41, 11262, 1101, 0, 95989, 66, 1101, 2, 0, 67, 1102, 1, 1083, 68, 1102, 302, 1, 69, 1102, 1, 1, 71, 1102, 1, 1087, 72, 1105, 1, 73, 0, 0, 0, 0, 31, 289516, 1102, 1, 61871, 66, 1102, 1, 1, 67, 1101, 1116, 0, 68, 1101, 556, 0, 69, 1101, 0, 1, 71, 1102, 1118, 1, 72, 1106, 0, 73, 1, -75, 5, 150261, 1101, 0, 77773, 66, 1102, 1, 1, 67, 1102, 1147, 1, 68, 1101, 0, 556, 69, 1102, 1, 1, 71, 1101, 1149, 0, 72, 1106, 0, 73, 1, 8669, 7, 7879, 1102, 62873, 1, 66, 1102, 1, 1, 67, 1101, 0, 1178, 68, 1102, 1, 556, 69, 1101, 1, 0, 71, 1101, 0, 1180, 72, 1105, 1, 73, 1, 160, 2, 74644, 1101, 0, 29077, 66, 1101, 0, 1, 67, 1102, 1209, 1, 68, 1102, 1, 556, 69, 1102, 1, 0, 71, 1101, 0, 1211, 72, 1106, 0, 73, 1, 1443, 1101, 100493, 0, 66, 1102, 3, 1, 67, 1101, 0, 1238, 68, 1102, 302, 1, 69, 1101, 1, 0, 71, 1102, 1244, 1, 72, 1106, 0, 73, 0, 0, 0, 0, 0, 0, 31, 217137, 1101, 3067, 0, 66, 1102, 1, 1, 67, 1101, 1273, 0, 68, 1101, 0, 556, 69, 1101, 0, 2, 71, 1102, 1, 1275, 72, 1105, 1, 73, 1, 10, 19, 213116, 2, 18661, 1102, 9187, 1, 66, 1101, 0, 2, 67, 1101, 1306, 0, 68, 1102, 302, 1, 69, 1101, 0, 1, 71, 1101, 0, 1310, 72, 1106, 0, 73, 0, 0, 0, 0, 13, 163342, 1101, 17551, 0, 66, 1101, 0, 3, 67, 1102, 1339, 1, 68, 1101, 0, 302, 69, 1101, 0, 1, 71, 1101, 1345, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 31, 72379, 1102, 1, 54377, 66, 1102, 2, 1, 67, 1102, 1, 1374, 68, 1101, 0, 302, 69, 1102, 1, 1, 71, 1101, 1378, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 48, 11483, 1102, 1, 7879, 66, 1102, 1, 3, 67, 1101, 1407, 0, 68, 1101, 0, 302, 69, 1101, 0, 1, 71, 1101, 1413, 0, 72, 1106, 0, 73, 0, 0, 0, 0, 0, 0, 14, 293313, 1102, 53279, 1, 66, 1102, 1, 4, 67, 1102, 1442, 1, 68, 1102, 302, 1, 69, 1102, 1, 1, 71, 1101, 0, 1450, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 0, 0, 2, 55983, 1101, 24571, 0, 66, 1102, 1, 1, 67, 1102, 1, 1479, 68, 1102, 1, 556, 69, 1102, 1, 0, 71, 1102, 1481, 1, 72, 1105, 1, 73, 1, 1215, 1102, 1, 101797, 66, 1102, 1, 1, 67, 1101, 1508, 0, 68, 1101, 556, 0, 69, 1101, 0, 0, 71, 1102, 1510, 1, 72, 1106, 0, 73, 1, 1934, 1102, 1, 4079, 66, 1102, 1, 1, 67, 1101, 0, 1537, 68, 1102, 556, 1, 69, 1101, 0, 8, 71, 1101, 0, 1539, 72, 1106, 0, 73, 1, 5, 12, 18374, 13, 81671, 8, 52653, 48, 22966, 15, 87793, 19, 53279, 19, 106558, 2, 37322, 1101, 0, 50957, 66, 1102, 1, 1, 67, 1101, 0, 1582, 68, 1102, 1, 556, 69, 1101, 2, 0, 71, 1102, 1, 1584, 72, 1105, 1, 73, 1, 499, 8, 35102, 5, 100174, 1102, 57383, 1, 66, 1101, 1, 0, 67, 1102, 1615, 1, 68, 1102, 556, 1, 69, 1102, 1, 3, 71, 1102, 1617, 1, 72, 1106, 0, 73, 1, 1, 7, 15758, 4, 20506, 5, 50087, 1101, 10253, 0, 66, 1102, 1, 3, 67, 1101, 0, 1650, 68, 1101, 0, 302, 69, 1102, 1, 1, 71, 1101, 1656, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 14, 195542, 1101, 61051, 0, 66, 1102, 1, 1, 67, 1101, 0, 1685, 68, 1101, 0, 556, 69, 1102, 1, 2, 71, 1101, 1687, 0, 72, 1105, 1, 73, 1, 125, 26, 170182, 19, 159837, 1102, 1, 81671, 66, 1101, 2, 0, 67, 1102, 1718, 1, 68, 1101, 302, 0, 69, 1102, 1, 1, 71, 1102, 1722, 1, 72, 1105, 1, 73, 0, 0, 0, 0, 8, 17551, 1101, 26489, 0, 66, 1102, 3, 1, 67, 1102, 1, 1751, 68, 1101, 302, 0, 69, 1102, 1, 1, 71, 1101, 0, 1757, 72, 1105, 1, 73, 0, 0, 0, 0, 0, 0, 31, 144758, 1102, 1, 87793, 66, 1102, 1, 2, 67, 1101, 0, 1786, 68, 1101, 0, 302, 69, 1101, 0, 1, 71, 1101, 1790, 0, 72, 1106, 0, 73, 0, 0, 0, 0, 11, 95989, 1102, 1, 84263, 66, 1102, 1, 1, 67, 1101, 1819, 0, 68, 1101, 0, 556, 69, 1101, 0, 1, 71, 1102, 1, 1821, 72, 1106, 0, 73, 1, 25, 46, 108754, 1101, 73679, 0, 66, 1102, 1, 1, 67, 1102, 1, 1850, 68, 1102, 1, 556, 69, 1101, 0, 0, 71, 1101, 1852, 0, 72, 1106, 0, 73, 1, 1410, 1101, 65071, 0, 66, 1102, 1, 1, 67, 1102, 1879, 1, 68, 1101, 556, 0, 69, 1102, 1, 1, 71, 1102, 1881, 1, 72, 1105, 1, 73, 1, 453643, 17, 59342, 1101, 0, 11483, 66, 1101, 2, 0, 67, 1102, 1, 1910, 68, 1102, 1, 302, 69, 1101, 1, 0, 71, 1102, 1, 1914, 72, 1106, 0, 73, 0, 0, 0, 0, 15, 175586, 1101, 0, 77687, 66, 1102, 1, 1, 67, 1101, 0, 1943, 68, 1102, 1, 556, 69, 1102, 6, 1, 71, 1101, 0, 1945, 72, 1106, 0, 73, 1, 21443, 11, 191978, 23, 100493, 23, 301479, 6, 26489, 6, 52978, 6, 79467, 1101, 72379, 0, 66, 1101, 4, 0, 67, 1102, 1984, 1, 68, 1102, 253, 1, 69, 1101, 0, 1, 71, 1102, 1, 1992, 72, 1106, 0, 73, 0, 0, 0, 0, 0, 0, 0, 0, 34, 17659, 1102, 17659, 1, 66, 1101, 0, 2, 67, 1102, 1, 2021, 68, 1101, 351, 0, 69, 1101, 0, 1, 71, 1101, 2025, 0, 72, 1105, 1, 73, 0, 0, 0, 0, 255, 77687, 1102, 1, 48883, 66, 1102, 1, 1, 67, 1102, 2054, 1, 68, 1102, 556, 1, 69, 1101, 0, 0, 71, 1101, 2056, 0, 72, 1105, 1, 73, 1, 1591, 1102, 1, 1877, 66, 1102, 1, 6, 67, 1101, 0, 2083, 68, 1101, 0, 253, 69, 1102, 1, 1, 71, 1101, 0, 2095, 72, 1106, 0, 73, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 26, 85091, 1102, 599, 1, 66, 1102, 1, 1, 67, 1101, 0, 2124, 68, 1101, 556, 0, 69, 1101, 1, 0, 71, 1102, 2126, 1, 72, 1106, 0, 73, 1, 51674, 41, 9385, 1102, 1, 76159, 66, 1101, 1, 0, 67, 1101, 2155, 0, 68, 1101, 556, 0, 69, 1102, 1, 1, 71, 1102, 2157, 1, 72, 1105, 1, 73, 1, -34362, 41, 3754, 1102, 23789, 1, 66, 1101, 1, 0, 67, 1101, 2186, 0, 68, 1102, 556, 1, 69, 1101, 0, 0, 71, 1102, 2188, 1, 72, 1105, 1, 73, 1, 1927, 1102, 1, 58151, 66, 1102, 1, 1, 67, 1101, 2215, 0, 68, 1101, 0, 556, 69, 1102, 1, 1, 71, 1101, 2217, 0, 72, 1106, 0, 73, 1, 67, 7, 23637];
+/// Words built into the translation, as `(start, end)`.
+const CODE: &[(usize, usize)] = &[(0, 10), (73, 133), (134, 140), (141, 161), (162, 169), (170, 194), (195, 234), (235, 240), (241, 253), (436, 470), (471, 564), (565, 571), (1916, 1943)];
+/// Each translated block, as `(start, end)`.
+const BLOCKS: &[(usize, usize)] = &[(0, 11), (73, 82), (82, 85), (85, 88), (88, 105), (105, 116), (116, 123), (123, 150), (150, 170), (170, 178), (178, 185), (185, 196), (196, 203), (203, 210), (210, 218), (218, 225), (225, 250), (250, 253), (436, 449), (449, 461), (461, 484), (484, 496), (496, 503), (503, 515), (515, 518), (518, 521), (521, 529), (529, 536), (536, 547), (547, 556), (556, 571), (1916, 1943)];
+
+impl<S: System<Word = I>> Compiled<S> {
+  /// Runs translated blocks from the current position until something stops them.
+  fn block(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let mut pc = self.program.position;
+    loop {
+      pc = match pc {
+        0 if !self.stale[0] => {
+          // 0: in [62]
+          let destination = 62;
+          let (event, changed) = self.input(0, destination)?;
+          if let Some(stop) = self.stop(0, 2, event, changed) { return Ok(stop); }
+          // 2: add [62], #11, [10]
+          self.set(10, I::wrapping_add(self.program.codes.get(62), 11));
+          // 6: arb #2219
+          self.program.relative_position = self.program.relative_position.wrapping_add(2219);
+          // 8: jt #1, [0]
+          self.address(8, self.program.codes.get(self.address(8, self.program.codes.get(10))?))?
+        }
+        73 if !self.stale[1] => {
+          // 73: in [64]
+          let destination = 64;
+          let (event, changed) = self.input(73, destination)?;
+          if let Some(stop) = self.stop(73, 75, event, changed) { return Ok(stop); }
+          // 75: eq [64], #-1, [62]
+          self.set(62, (self.program.codes.get(64) == -1) as I);
+          // 79: jf [62], #88
+          if self.program.codes.get(62) == 0 { 88 } else { 82 }
+        }
+        82 if !self.stale[2] => {
+          // 82: jf [61], #170
+          if self.program.codes.get(61) == 0 { 170 } else { 85 }
+        }
+        85 if !self.stale[3] => {
+          // 85: jt #1, #73
+          73
+        }
+        88 if !self.stale[4] => {
+          // 88: in [65]
+          let destination = 65;
+          let (event, changed) = self.input(88, destination)?;
+          if let Some(stop) = self.stop(88, 90, event, changed) { return Ok(stop); }
+          // 90: mul #1, [64], rb+1
+          let value = I::wrapping_mul(1, self.program.codes.get(64));
+          if self.store(self.relative(90, 1)?, value) { self.program.position = 94; return Ok(None); }
+          // 94: add [66], #0, rb+2
+          let value = I::wrapping_add(self.program.codes.get(66), 0);
+          if self.store(self.relative(94, 2)?, value) { self.program.position = 98; return Ok(None); }
+          // 98: add #0, #105, rb+0
+          let value = I::wrapping_add(0, 105);
+          if self.store(self.relative(98, 0)?, value) { self.program.position = 102; return Ok(None); }
+          // 102: jt #1, #436
+          436
+        }
+        105 if !self.stale[5] => {
+          // 105: add rb+1, #-1, [64]
+          self.set(64, I::wrapping_add(self.program.codes.get(self.relative(105, 1)?), -1));
+          // 109: lt [64], #0, [62]
+          self.set(62, (self.program.codes.get(64) < 0) as I);
+          // 113: jt [62], #73
+          if self.program.codes.get(62) != 0 { 73 } else { 116 }
+        }
+        116 if !self.stale[6] => {
+          // 116: lt [64], [67], [62]
+          self.set(62, (self.program.codes.get(64) < self.program.codes.get(67)) as I);
+          // 120: jf [62], #73
+          if self.program.codes.get(62) == 0 { 73 } else { 123 }
+        }
+        123 if !self.stale[7] => {
+          // 123: mul [64], #2, [133]
+          self.set(133, I::wrapping_mul(self.program.codes.get(64), 2));
+          // 127: add [133], [68], [133]
+          self.set(133, I::wrapping_add(self.program.codes.get(133), self.program.codes.get(68)));
+          // 131: add #0, [0], [62]
+          self.set(62, I::wrapping_add(0, self.program.codes.get(self.address(131, self.program.codes.get(133))?)));
+          // 135: add [133], #1, [140]
+          self.set(140, I::wrapping_add(self.program.codes.get(133), 1));
+          // 139: eq [0], [65], [63]
+          self.set(63, (self.program.codes.get(self.address(139, self.program.codes.get(140))?) == self.program.codes.get(65)) as I);
+          // 143: mul [63], [62], [62]
+          self.set(62, I::wrapping_mul(self.program.codes.get(63), self.program.codes.get(62)));
+          // 147: jt [62], #73
+          if self.program.codes.get(62) != 0 { 73 } else { 150 }
+        }
+        150 if !self.stale[8] => {
+          // 150: mul [64], #2, [161]
+          self.set(161, I::wrapping_mul(self.program.codes.get(64), 2));
+          // 154: add [161], [68], [161]
+          self.set(161, I::wrapping_add(self.program.codes.get(161), self.program.codes.get(68)));
+          // 158: mul #1, #1, [0]
+          let value = I::wrapping_mul(1, 1);
+          if self.store(self.address(158, self.program.codes.get(161))?, value) { self.program.position = 162; return Ok(None); }
+          // 162: add [161], #1, [169]
+          self.set(169, I::wrapping_add(self.program.codes.get(161), 1));
+          // 166: mul [65], #1, [0]
+          let value = I::wrapping_mul(self.program.codes.get(65), 1);
+          if self.store(self.address(166, self.program.codes.get(169))?, value) { self.program.position = 170; return Ok(None); }
+          170
+        }
+        170 if !self.stale[9] => {
+          // 170: add #1, #0, [61]
+          self.set(61, I::wrapping_add(1, 0));
+          // 174: add #0, #0, [63]
+          self.set(63, I::wrapping_add(0, 0));
+          178
+        }
+        178 if !self.stale[10] => {
+          // 178: lt [63], [67], [62]
+          self.set(62, (self.program.codes.get(63) < self.program.codes.get(67)) as I);
+          // 182: jf [62], #203
+          if self.program.codes.get(62) == 0 { 203 } else { 185 }
+        }
+        185 if !self.stale[11] => {
+          // 185: mul [63], #2, [194]
+          self.set(194, I::wrapping_mul(self.program.codes.get(63), 2));
+          // 189: add [68], [194], [194]
+          self.set(194, I::wrapping_add(self.program.codes.get(68), self.program.codes.get(194)));
+          // 193: jf [0], #73
+          self.program.position = 193;
+          return self.interpret();
+        }
+        196 if !self.stale[12] => {
+          // 196: add [63], #1, [63]
+          self.set(63, I::wrapping_add(self.program.codes.get(63), 1));
+          // 200: jt #1, #178
+          178
+        }
+        203 if !self.stale[13] => {
+          // 203: add #0, #210, rb+0
+          let value = I::wrapping_add(0, 210);
+          if self.store(self.relative(203, 0)?, value) { self.program.position = 207; return Ok(None); }
+          // 207: jf #0, [69]
+          self.address(207, self.program.codes.get(69))?
+        }
+        210 if !self.stale[14] => {
+          // 210: add rb+1, #0, [70]
+          self.set(70, I::wrapping_add(self.program.codes.get(self.relative(210, 1)?), 0));
+          // 214: mul #1, #0, [63]
+          self.set(63, I::wrapping_mul(1, 0));
+          218
+        }
+        218 if !self.stale[15] => {
+          // 218: lt [63], [71], [62]
+          self.set(62, (self.program.codes.get(63) < self.program.codes.get(71)) as I);
+          // 222: jf [62], #250
+          if self.program.codes.get(62) == 0 { 250 } else { 225 }
+        }
+        225 if !self.stale[16] => {
+          // 225: mul [63], #2, [234]
+          self.set(234, I::wrapping_mul(self.program.codes.get(63), 2));
+          // 229: add [72], [234], [234]
+          self.set(234, I::wrapping_add(self.program.codes.get(72), self.program.codes.get(234)));
+          // 233: out [0]
+          let event = self.program.system.take_output(self.program.codes.get(self.address(233, self.program.codes.get(234))?));
+          if let Some(stop) = self.stop(233, 235, event, false) { return Ok(stop); }
+          // 235: add #1, [234], [240]
+          self.set(240, I::wrapping_add(1, self.program.codes.get(234)));
+          // 239: out [0]
+          let event = self.program.system.take_output(self.program.codes.get(self.address(239, self.program.codes.get(240))?));
+          if let Some(stop) = self.stop(239, 241, event, false) { return Ok(stop); }
+          // 241: out [70]
+          let event = self.program.system.take_output(self.program.codes.get(70));
+          if let Some(stop) = self.stop(241, 243, event, false) { return Ok(stop); }
+          // 243: add [63], #1, [63]
+          self.set(63, I::wrapping_add(self.program.codes.get(63), 1));
+          // 247: jt #1, #218
+          218
+        }
+        250 if !self.stale[17] => {
+          // 250: jt #1, #73
+          73
+        }
+        436 if !self.stale[18] => {
+          // 436: arb #8
+          self.program.relative_position = self.program.relative_position.wrapping_add(8);
+          // 438: mul rb-6, #10, rb-5
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(438, -6)?), 10);
+          if self.store(self.relative(438, -5)?, value) { self.program.position = 442; return Ok(None); }
+          // 442: lt rb-7, rb-5, rb-5
+          let value = (self.program.codes.get(self.relative(442, -7)?) < self.program.codes.get(self.relative(442, -5)?)) as I;
+          if self.store(self.relative(442, -5)?, value) { self.program.position = 446; return Ok(None); }
+          // 446: jt rb-5, #521
+          if self.program.codes.get(self.relative(446, -5)?) != 0 { 521 } else { 449 }
+        }
+        449 if !self.stale[19] => {
+          // 449: mul #0, #1, rb-4
+          let value = I::wrapping_mul(0, 1);
+          if self.store(self.relative(449, -4)?, value) { self.program.position = 453; return Ok(None); }
+          // 453: add #0, #0, rb-3
+          let value = I::wrapping_add(0, 0);
+          if self.store(self.relative(453, -3)?, value) { self.program.position = 457; return Ok(None); }
+          // 457: add #0, #51, rb-2
+          let value = I::wrapping_add(0, 51);
+          if self.store(self.relative(457, -2)?, value) { self.program.position = 461; return Ok(None); }
+          461
+        }
+        461 if !self.stale[20] => {
+          // 461: add rb-2, #-1, rb-2
+          let value = I::wrapping_add(self.program.codes.get(self.relative(461, -2)?), -1);
+          if self.store(self.relative(461, -2)?, value) { self.program.position = 465; return Ok(None); }
+          // 465: add rb-2, #385, [470]
+          self.set(470, I::wrapping_add(self.program.codes.get(self.relative(465, -2)?), 385));
+          // 469: mul [0], #1, rb-1
+          let value = I::wrapping_mul(self.program.codes.get(self.address(469, self.program.codes.get(470))?), 1);
+          if self.store(self.relative(469, -1)?, value) { self.program.position = 473; return Ok(None); }
+          // 473: mul rb-3, #2, rb-3
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(473, -3)?), 2);
+          if self.store(self.relative(473, -3)?, value) { self.program.position = 477; return Ok(None); }
+          // 477: lt rb-7, rb-1, rb-5
+          let value = (self.program.codes.get(self.relative(477, -7)?) < self.program.codes.get(self.relative(477, -1)?)) as I;
+          if self.store(self.relative(477, -5)?, value) { self.program.position = 481; return Ok(None); }
+          // 481: jt rb-5, #496
+          if self.program.codes.get(self.relative(481, -5)?) != 0 { 496 } else { 484 }
+        }
+        484 if !self.stale[21] => {
+          // 484: add rb-3, #1, rb-3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(484, -3)?), 1);
+          if self.store(self.relative(484, -3)?, value) { self.program.position = 488; return Ok(None); }
+          // 488: mul #-1, rb-1, rb-5
+          let value = I::wrapping_mul(-1, self.program.codes.get(self.relative(488, -1)?));
+          if self.store(self.relative(488, -5)?, value) { self.program.position = 492; return Ok(None); }
+          // 492: add rb-7, rb-5, rb-7
+          let value = I::wrapping_add(self.program.codes.get(self.relative(492, -7)?), self.program.codes.get(self.relative(492, -5)?));
+          if self.store(self.relative(492, -7)?, value) { self.program.position = 496; return Ok(None); }
+          496
+        }
+        496 if !self.stale[22] => {
+          // 496: lt rb-3, rb-6, rb-5
+          let value = (self.program.codes.get(self.relative(496, -3)?) < self.program.codes.get(self.relative(496, -6)?)) as I;
+          if self.store(self.relative(496, -5)?, value) { self.program.position = 500; return Ok(None); }
+          // 500: jt rb-5, #515
+          if self.program.codes.get(self.relative(500, -5)?) != 0 { 515 } else { 503 }
+        }
+        503 if !self.stale[23] => {
+          // 503: mul #-1, rb-6, rb-5
+          let value = I::wrapping_mul(-1, self.program.codes.get(self.relative(503, -6)?));
+          if self.store(self.relative(503, -5)?, value) { self.program.position = 507; return Ok(None); }
+          // 507: add rb-3, rb-5, rb-3
+          let value = I::wrapping_add(self.program.codes.get(self.relative(507, -3)?), self.program.codes.get(self.relative(507, -5)?));
+          if self.store(self.relative(507, -3)?, value) { self.program.position = 511; return Ok(None); }
+          // 511: add rb-1, rb-4, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(511, -1)?), self.program.codes.get(self.relative(511, -4)?));
+          if self.store(self.relative(511, -4)?, value) { self.program.position = 515; return Ok(None); }
+          515
+        }
+        515 if !self.stale[24] => {
+          // 515: jt rb-2, #461
+          if self.program.codes.get(self.relative(515, -2)?) != 0 { 461 } else { 518 }
+        }
+        518 if !self.stale[25] => {
+          // 518: jt #1, #547
+          547
+        }
+        521 if !self.stale[26] => {
+          // 521: mul #1, #-1, rb-4
+          let value = I::wrapping_mul(1, -1);
+          if self.store(self.relative(521, -4)?, value) { self.program.position = 525; return Ok(None); }
+          // 525: mul rb-6, #-1, rb-6
+          let value = I::wrapping_mul(self.program.codes.get(self.relative(525, -6)?), -1);
+          if self.store(self.relative(525, -6)?, value) { self.program.position = 529; return Ok(None); }
+          529
+        }
+        529 if !self.stale[27] => {
+          // 529: lt rb-7, #0, rb-5
+          let value = (self.program.codes.get(self.relative(529, -7)?) < 0) as I;
+          if self.store(self.relative(529, -5)?, value) { self.program.position = 533; return Ok(None); }
+          // 533: jt rb-5, #547
+          if self.program.codes.get(self.relative(533, -5)?) != 0 { 547 } else { 536 }
+        }
+        536 if !self.stale[28] => {
+          // 536: add rb-7, rb-6, rb-7
+          let value = I::wrapping_add(self.program.codes.get(self.relative(536, -7)?), self.program.codes.get(self.relative(536, -6)?));
+          if self.store(self.relative(536, -7)?, value) { self.program.position = 540; return Ok(None); }
+          // 540: add rb-4, #1, rb-4
+          let value = I::wrapping_add(self.program.codes.get(self.relative(540, -4)?), 1);
+          if self.store(self.relative(540, -4)?, value) { self.program.position = 544; return Ok(None); }
+          // 544: jf #0, #529
+          529
+        }
+        547 if !self.stale[29] => {
+          // 547: mul #1, rb-4, rb-7
+          let value = I::wrapping_mul(1, self.program.codes.get(self.relative(547, -4)?));
+          if self.store(self.relative(547, -7)?, value) { self.program.position = 551; return Ok(None); }
+          // 551: arb #-8
+          self.program.relative_position = self.program.relative_position.wrapping_add(-8);
+          // 553: jf #0, rb+0
+          self.address(553, self.program.codes.get(self.relative(553, 0)?))?
+        }
+        556 if !self.stale[30] => {
+          // 556: arb #1
+          self.program.relative_position = self.program.relative_position.wrapping_add(1);
+          // 558: add #1, [68], [564]
+          self.set(564, I::wrapping_add(1, self.program.codes.get(68)));
+          // 562: add #0, [0], rb+0
+          let value = I::wrapping_add(0, self.program.codes.get(self.address(562, self.program.codes.get(564))?));
+          if self.store(self.relative(562, 0)?, value) { self.program.position = 566; return Ok(None); }
+          // 566: arb #-1
+          self.program.relative_position = self.program.relative_position.wrapping_add(-1);
+          // 568: jt #1, rb+0
+          self.address(568, self.program.codes.get(self.relative(568, 0)?))?
+        }
+        1916 if !self.stale[31] => {
+          // 1916: add #0, #77687, [66]
+          self.set(66, I::wrapping_add(0, 77687));
+          // 1920: mul #1, #1, [67]
+          self.set(67, I::wrapping_mul(1, 1));
+          // 1924: add #0, #1943, [68]
+          self.set(68, I::wrapping_add(0, 1943));
+          // 1928: mul #1, #556, [69]
+          self.set(69, I::wrapping_mul(1, 556));
+          // 1932: mul #6, #1, [71]
+          self.set(71, I::wrapping_mul(6, 1));
+          // 1936: add #0, #1945, [72]
+          self.set(72, I::wrapping_add(0, 1945));
+          // 1940: jf #0, #73
+          73
+        }
+        _ => {
+          self.program.position = pc;
+          return self.interpret();
+        }
+      };
+    }
+  }
+}
@@ -0,0 +1,102 @@
+//! Compares the plain interpreter against the decode cache on day19's 50x50 scan and day23's
+//! network. Run with `cargo bench --features part-one --bench decode_cache`.
+use adventofcode2019::intcode::{Event, Program, I};
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 5;
+
+fn main() {
+  let day19 = load(include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/inputs/day19.txt"
+  )));
+  let day23 = load(include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/inputs/day23.txt"
+  )));
+  compare(
+    "day19 50x50 scan",
+    || scan(day19.clone()),
+    || scan(day19.clone().cached()),
+  );
+  compare(
+    "day23 network",
+    || network(&day23),
+    || network(&day23.clone().cached()),
+  );
+}
+
+fn load(input: &str) -> Program {
+  input.parse().unwrap()
+}
+
+fn compare(name: &str, plain: impl Fn() -> I, cached: impl Fn() -> I) {
+  let (plain_answer, plain_time) = time(plain);
+  let (cached_answer, cached_time) = time(cached);
+  assert_eq!(plain_answer, cached_answer);
+  println!(
+    "{:<20} plain: {:>10.2?}  cached: {:>10.2?}  speedup: {:.2}x",
+    name,
+    plain_time,
+    cached_time,
+    plain_time.as_secs_f64() / cached_time.as_secs_f64()
+  );
+}
+
+/// Best of `ROUNDS` runs.
+fn time(f: impl Fn() -> I) -> (I, Duration) {
+  (0..ROUNDS)
+    .map(|_| {
+      let start = Instant::now();
+      let answer = f();
+      (answer, start.elapsed())
+    })
+    .min_by_key(|(_, elapsed)| *elapsed)
+    .unwrap()
+}
+
+/// Counts the points affected by the tractor beam, resetting the program for each one.
+fn scan(mut program: Program) -> I {
+  let mut affected = 0;
+  for y in 0..50 {
+    for x in 0..50 {
+      program.reset();
+      program.system.input.extend(&[x, y]);
+      program.run().unwrap();
+      affected += program.system.output[0];
+    }
+  }
+  affected
+}
+
+/// Runs 50 NICs round robin with a NAT that wakes NIC 0 whenever a round passes without
+/// traffic, returning the first y value the NAT delivers twice in a row.
+fn network(base: &Program) -> I {
+  let mut nics = (0..50).map(|i| base.clone().i(i)).collect::<Vec<_>>();
+  let (mut nat, mut last_delivered) = (None, None);
+  loop {
+    let mut idle = true;
+    for n in 0..nics.len() {
+      if nics[n].system.input.is_empty() {
+        nics[n].system.input.push_back(-1);
+      }
+      while nics[n].run_to_event().unwrap() != Event::AskForInputAgain {}
+      let output = nics[n].system.output.drain(..).collect::<Vec<_>>();
+      for packet in output.chunks(3) {
+        idle = false;
+        match packet {
+          [255, x, y] => nat = Some((*x, *y)),
+          [address, x, y] => nics[*address as usize].system.input.extend(&[*x, *y]),
+          _ => unreachable!(),
+        }
+      }
+    }
+    if let (true, Some((x, y))) = (idle, nat) {
+      if last_delivered == Some(y) {
+        return y;
+      }
+      last_delivered = Some(y);
+      nics[0].system.input.extend(&[x, y]);
+    }
+  }
+}
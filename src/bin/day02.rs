@@ -1,40 +1,26 @@
+use adventofcode2019::intcode::{Patch, Program, I};
+
 fn main() {
-  let ints = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day02.txt"));
-  let ints = ints
-    .split(",")
-    .filter_map(|s| s.parse::<usize>().ok())
-    .collect::<Vec<_>>();
-  let mut i = ints.clone();
-  i[1] = 12;
-  i[2] = 2;
-  process_ints(&mut i);
-  println!("Value as position 0 is {}", i[0]);
-  println!("Part two ints is {:?}", part_two(&ints));
+  let input = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day02.txt"));
+  let program = input.parse::<Program>().unwrap();
+  println!("Value as position 0 is {}", run(&program, 12, 2));
+  println!("Part two ints is {:?}", part_two(&program));
 }
 
-fn process_ints(ints: &mut [usize]) {
-  let mut position = 0;
-  loop {
-    let result = match ints[position] {
-      99 => break,
-      2 => ints[ints[position + 1]] * ints[ints[position + 2]],
-      1 => ints[ints[position + 1]] + ints[ints[position + 2]],
-      _ => panic!("Invalid code {} at position {}", ints[position], position),
-    };
-    let third_position = ints[position + 3];
-    ints[third_position] = result;
-    position += 4;
-  }
+/// Runs a copy of the program with `noun` and `verb` patched in and returns address 0.
+fn run(program: &Program, noun: I, verb: I) -> I {
+  let mut program = program.clone().patched(vec![
+    Patch::new(1, noun).named("noun"),
+    Patch::new(2, verb).named("verb"),
+  ]);
+  program.run().unwrap();
+  program.codes[0]
 }
 
-fn part_two(ints: &[usize]) -> (usize, usize) {
+fn part_two(program: &Program) -> (I, I) {
   for noun in 0..100 {
     for verb in 0..100 {
-      let mut i = ints.to_vec();
-      i[1] = noun;
-      i[2] = verb;
-      process_ints(&mut i);
-      if i[0] == 19690720 {
+      if run(program, noun, verb) == 19690720 {
         return (noun, verb);
       }
     }
@@ -44,16 +30,16 @@ fn part_two(ints: &[usize]) -> (usize, usize) {
 
 #[test]
 fn test_process_ints() {
-  let mut ints = vec![1, 0, 0, 0, 99];
-  process_ints(&mut ints);
-  assert_eq!(ints, vec![2, 0, 0, 0, 99]);
-  let mut ints = vec![2, 3, 0, 3, 99];
-  process_ints(&mut ints);
-  assert_eq!(ints, vec![2, 3, 0, 6, 99]);
-  let mut ints = vec![2, 4, 4, 5, 99, 0];
-  process_ints(&mut ints);
-  assert_eq!(ints, vec![2, 4, 4, 5, 99, 9801]);
-  let mut ints = vec![1, 1, 1, 4, 99, 5, 6, 0, 99];
-  process_ints(&mut ints);
-  assert_eq!(ints, vec![30, 1, 1, 4, 2, 5, 6, 0, 99]);
+  let process = |codes: &str| {
+    let mut program = codes.parse::<Program>().unwrap();
+    program.run().unwrap();
+    program.codes.words(0, program.original_codes.len())
+  };
+  assert_eq!(process("1,0,0,0,99"), vec![2, 0, 0, 0, 99]);
+  assert_eq!(process("2,3,0,3,99"), vec![2, 3, 0, 6, 99]);
+  assert_eq!(process("2,4,4,5,99,0"), vec![2, 4, 4, 5, 99, 9801]);
+  assert_eq!(
+    process("1,1,1,4,99,5,6,0,99"),
+    vec![30, 1, 1, 4, 2, 5, 6, 0, 99]
+  );
 }
@@ -1,11 +1,40 @@
-use std::io::Read;
-use std::str::FromStr;
+use adventofcode2019::intcode::{Event, Program, Snapshot, System, I};
+use std::collections::VecDeque;
+use std::io::BufRead;
 
 fn main() {
-  let input =
-    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day25.txt")).replace("\n", "");
+  let input = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day25.txt"));
   let mut io = input.parse::<Program<Ship>>().unwrap();
-  io.run();
+  // `save <file>` and `load <file>` lines snapshot the droid instead of being sent to it.
+  loop {
+    match io.run_to_event() {
+      Ok(Event::AskForInputAgain) => {}
+      Ok(_) => break,
+      Err(e) => {
+        println!("{}", e);
+        break;
+      }
+    }
+    match io.system.command.take() {
+      Some(Command::Save(path)) => match io.snapshot().save(&path) {
+        Ok(()) => println!("Saved to {}", path),
+        Err(e) => println!("Unable to save {}: {}", path, e),
+      },
+      Some(Command::Load(path)) => match Snapshot::load(&path) {
+        Ok(snapshot) => {
+          io.restore(&snapshot);
+          println!("Loaded {}\nCommand?", path);
+        }
+        Err(e) => println!("Unable to load {}: {}", path, e),
+      },
+      None => break,
+    }
+  }
+}
+
+enum Command {
+  Save(String),
+  Load(String),
 }
 
 //gmail: monolith, astrolabe, planetoid, fuel cell
@@ -37,296 +66,105 @@ fn main() {
 //}
 
 #[derive(Default)]
-struct Ship;
+struct Ship {
+  line: VecDeque<I>,
+  command: Option<Command>,
+}
 
 impl System for Ship {
-  fn send_input(&mut self) -> Option<i64> {
-    let mut buf = [0];
-    std::io::stdin().read(&mut buf).map(|_| buf[0] as i64).ok()
+  type Word = I;
+
+  fn send_input(&mut self) -> Event {
+    if self.line.is_empty() {
+      let mut line = String::new();
+      match std::io::stdin().lock().read_line(&mut line) {
+        Ok(0) => return Event::EndOfInput,
+        Ok(_) => {}
+        Err(_) => return Event::AskForInputAgain,
+      }
+      let mut words = line.split_whitespace();
+      match (words.next(), words.next()) {
+        (Some("save"), Some(path)) => self.command = Some(Command::Save(path.into())),
+        (Some("load"), Some(path)) => self.command = Some(Command::Load(path.into())),
+        _ => self.line.extend(line.bytes().map(|b| b as I)),
+      }
+    }
+    match self.line.pop_front() {
+      Some(i) => Event::Input(i),
+      None => Event::AskForInputAgain,
+    }
   }
 
-  fn take_output(&mut self, output: i64) {
+  fn take_output(&mut self, output: I) -> Event {
     if output > 127 {
       println!("{}", output);
     } else {
       let c = output as u8 as char;
       print!("{}", c);
     }
+    Event::Output(output)
   }
-}
-
-type I = i64;
 
-trait System: Default {
-  fn send_input(&mut self) -> Option<I>;
-  fn take_output(&mut self, output: I);
-  fn end(&mut self) -> Option<I> {
-    None
-  }
-  fn reset(&mut self) {}
-}
-
-#[derive(Clone, Debug)]
-struct Program<S: System> {
-  name: String,
-  codes: Vec<I>,
-  original_codes: Vec<I>,
-  position: usize,
-  relative_position: I,
-  reset_after_run: bool,
-  system: S,
-}
-
-impl<S: System> Program<S> {
-  #[allow(unused)]
-  fn name(mut self, s: impl Into<String>) -> Self {
-    self.name = s.into();
-    self
-  }
-  fn reset(&mut self) {
-    self.position = 0;
-    self.relative_position = 0;
-    self
-      .original_codes
-      .reserve(self.codes.len() - self.original_codes.len());
-    for _ in 0..self.codes.len() - self.original_codes.len() {
-      self.original_codes.push(0);
-    }
-    self
-      .codes
-      .iter_mut()
-      .zip(self.original_codes.iter().copied())
-      .for_each(|(c, o)| {
-        *c = o;
-      });
-    self.system.reset();
-  }
-  fn run(&mut self) -> Option<I> {
-    loop {
-      let opcode = (&self.codes[self.position..(self.position + 4).min(self.codes.len())]).into();
-      if let OpCode::Break = opcode {
-        break;
-      }
-      let cont = self.apply(opcode);
-      self.move_position(opcode);
-      if !cont {
-        break;
-      }
-    }
-    let output = self.system.end();
-    if self.reset_after_run {
-      self.reset();
-    }
-    output
+  /// The rest of a line that has only been partly sent.
+  fn save_state(&self) -> Vec<I> {
+    self.line.iter().copied().collect()
   }
-  fn move_position(&mut self, opcode: OpCode) {
-    let codes = self.codes.as_slice();
-    match opcode {
-      OpCode::Add(_, _, _) | OpCode::Multiply(_, _, _) => self.position += 4,
-      OpCode::Input(_) | OpCode::Output(_) | OpCode::AdjustRelativeBase(_) => self.position += 2,
-      OpCode::Break => unreachable!(),
-      OpCode::LessThan(_, _, _) | OpCode::Equals(_, _, _) => self.position += 4,
-      OpCode::JumpIfTrue(p1, p2) => {
-        if p1.value(codes, self.relative_position) != 0 {
-          self.position = p2.value(codes, self.relative_position) as usize;
-        } else {
-          self.position += 3;
-        }
-      }
-      OpCode::JumpIfFalse(p1, p2) => {
-        if p1.value(codes, self.relative_position) == 0 {
-          self.position = p2.value(codes, self.relative_position) as usize;
-        } else {
-          self.position += 3
-        }
-      }
-    };
-    //println!("{}: Moving position to {}", self.name, self.position);
-  }
-  fn apply(&mut self, opcode: OpCode) -> bool {
-    //println!(
-    //  "{}: Applying {:?} (len: {})",
-    //  self.name,
-    //  opcode,
-    //  self.codes.len(),
-    //);
-    let relative_position = self.relative_position;
-    match opcode {
-      OpCode::Add(p1, p2, p3) => {
-        let p3_position = p3.position(relative_position);
-        self.ensure_space(p3_position);
-        self.codes[p3_position] =
-          p1.value(&self.codes, relative_position) + p2.value(&self.codes, relative_position)
-      }
-      OpCode::Multiply(p1, p2, p3) => {
-        let p3_position = p3.position(relative_position);
-        self.ensure_space(p3_position);
-        self.codes[p3_position] =
-          p1.value(&self.codes, relative_position) * p2.value(&self.codes, relative_position)
-      }
-      OpCode::Input(p1) => {
-        let p1_position = p1.position(relative_position);
-        self.ensure_space(p1_position);
-        match self.system.send_input() {
-          Some(i) => self.codes[p1_position] = i,
-          None => return false,
-        }
-      }
-      OpCode::Output(p1) => {
-        let value = p1.value(&self.codes, relative_position);
-        self.system.take_output(value);
-      }
-      OpCode::Break => unreachable!(),
-      OpCode::JumpIfTrue(_, _) | OpCode::JumpIfFalse(_, _) => {}
-      OpCode::LessThan(p1, p2, p3) => {
-        let p3_position = p3.position(relative_position);
-        self.ensure_space(p3_position);
-        self.codes[p3_position] =
-          if p1.value(&self.codes, relative_position) < p2.value(&&self.codes, relative_position) {
-            1
-          } else {
-            0
-          }
-      }
-      OpCode::Equals(p1, p2, p3) => {
-        let p3_position = p3.position(relative_position);
-        self.ensure_space(p3_position);
-        self.codes[p3_position] =
-          if p1.value(&self.codes, relative_position) == p2.value(&self.codes, relative_position) {
-            1
-          } else {
-            0
-          }
-      }
-      OpCode::AdjustRelativeBase(p1) => {
-        self.relative_position += p1.value(&self.codes, relative_position)
-      }
-    }
-    true
-  }
-  fn ensure_space(&mut self, position: usize) {
-    if self.codes.len() <= position {
-      //println!("Extending from len {} to {}", self.codes.len(), position);
-      self.codes.reserve(position - self.codes.len());
-      while self.codes.len() <= position {
-        self.codes.push(0);
-      }
-    }
-  }
-}
 
-impl<S: System> FromStr for Program<S> {
-  type Err = ();
-
-  fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let codes = s
-      .split(",")
-      .filter_map(|s| {
-        s.parse::<I>()
-          .map_err(|_| println!("Unable to parse {}", s))
-          .ok()
-      })
-      .collect::<Vec<_>>();
-    Ok(Self {
-      name: "base".into(),
-      original_codes: codes.clone(),
-      codes,
-      position: 0,
-      relative_position: 0,
-      reset_after_run: false,
-      system: S::default(),
-    })
+  fn restore_state(&mut self, state: &[I]) {
+    self.line = state.iter().copied().collect();
   }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum ParameterMode {
-  Position(I),
-  Immediate(I),
-  Relative(I),
+/// Walks to the sick bay, takes the brochure and comes back, recorded with
+/// `intcode record inputs/day25.txt inputs/day25.transcript`.
+#[test]
+fn test_transcript() {
+  use adventofcode2019::intcode::{Entry, Transcript};
+  let input = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day25.txt"));
+  let transcript = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/inputs/day25.transcript"
+  ));
+  let mut transcript = transcript.parse::<Transcript>().unwrap();
+  let program = input.parse::<Program>().unwrap();
+  assert_eq!(transcript.replay(program.clone()), Ok(()));
+  let text = transcript
+    .outputs()
+    .map(|&o| o as u8 as char)
+    .collect::<String>();
+  assert!(text.contains("You take the space law space brochure."));
+
+  // Going north instead of south leads somewhere else.
+  let first = transcript
+    .entries
+    .iter()
+    .position(|e| matches!(e, Entry::Input(_)))
+    .unwrap();
+  transcript.entries[first] = Entry::Input(b'n' as I);
+  assert!(transcript.replay(program).is_err());
 }
 
-impl ParameterMode {
-  fn value(self, codes: &[I], relative_position: I) -> I {
-    let value = match self {
-      Self::Position(i) => codes[i as usize],
-      Self::Immediate(i) => i,
-      Self::Relative(i) => codes[(i + relative_position) as usize],
-    };
-    //println!("Value of {:?} ({}) is {}", self, relative_position, value);
-    value
-  }
-  fn position(self, relative_position: I) -> usize {
-    let position = match self {
-      Self::Position(i) | Self::Immediate(i) => i as usize,
-      Self::Relative(i) => (i + relative_position) as usize,
-    };
-    //println!("Position of {:?} is {}", self, position);
-    position
+#[test]
+fn test_snapshot_mid_line() {
+  let input = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/day25.txt"));
+  let mut program = input.parse::<Program<Ship>>().unwrap();
+  program
+    .system
+    .line
+    .extend(b"north\n".iter().map(|&b| b as I));
+  while program.system.line.len() > 3 {
+    program.step().unwrap();
   }
-}
-
-impl From<(I, I)> for ParameterMode {
-  fn from((mode, value): (I, I)) -> Self {
-    match mode {
-      0 => Self::Position(value),
-      1 => Self::Immediate(value),
-      2 => Self::Relative(value),
-      _ => unreachable!(),
-    }
+  let snapshot = program.snapshot().to_string().parse::<Snapshot>().unwrap();
+  let mut restored = input.parse::<Program<Ship>>().unwrap();
+  restored.restore(&snapshot);
+  assert_eq!(restored.system.line, program.system.line);
+  // Both finish sending the line the same way.
+  while !program.system.line.is_empty() {
+    program.step().unwrap();
   }
-}
-
-#[derive(Copy, Clone, Debug)]
-enum OpCode {
-  Add(ParameterMode, ParameterMode, ParameterMode),
-  Multiply(ParameterMode, ParameterMode, ParameterMode),
-  Input(ParameterMode),
-  Output(ParameterMode),
-  Break,
-  JumpIfTrue(ParameterMode, ParameterMode),
-  JumpIfFalse(ParameterMode, ParameterMode),
-  LessThan(ParameterMode, ParameterMode, ParameterMode),
-  Equals(ParameterMode, ParameterMode, ParameterMode),
-  AdjustRelativeBase(ParameterMode),
-}
-
-impl From<&[I]> for OpCode {
-  fn from(codes: &[I]) -> Self {
-    //println!("Making OpCode from {:?}", codes);
-    let i = codes[0];
-    let code = i % 100;
-    let param1 = i / 100 % 10;
-    let param2 = i / 1000 % 10;
-    let param3 = i / 10000 % 10;
-    match code {
-      1 => Self::Add(
-        (param1, codes[1]).into(),
-        (param2, codes[2]).into(),
-        (param3, codes[3]).into(),
-      ),
-      2 => Self::Multiply(
-        (param1, codes[1]).into(),
-        (param2, codes[2]).into(),
-        (param3, codes[3]).into(),
-      ),
-      3 => Self::Input((param1, codes[1]).into()),
-      4 => Self::Output((param1, codes[1]).into()),
-      5 => Self::JumpIfTrue((param1, codes[1]).into(), (param2, codes[2]).into()),
-      6 => Self::JumpIfFalse((param1, codes[1]).into(), (param2, codes[2]).into()),
-      7 => Self::LessThan(
-        (param1, codes[1]).into(),
-        (param2, codes[2]).into(),
-        (param3, codes[3]).into(),
-      ),
-      8 => Self::Equals(
-        (param1, codes[1]).into(),
-        (param2, codes[2]).into(),
-        (param3, codes[3]).into(),
-      ),
-      9 => Self::AdjustRelativeBase((param1, codes[1]).into()),
-      99 => Self::Break,
-      _ => unreachable!(),
-    }
+  while !restored.system.line.is_empty() {
+    restored.step().unwrap();
   }
+  assert_eq!(restored.snapshot(), program.snapshot());
 }
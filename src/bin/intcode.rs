@@ -0,0 +1,324 @@
+use adventofcode2019::intcode::{
+  assemble, disassemble, parse_annotated, translate, Cfg, Debugger, Diff, Event, Limits, OpCode,
+  Patch, Program, Snapshot, Symbolic, Transcript, I,
+};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+fn main() {
+  let mut args = env::args().skip(1).collect::<Vec<_>>();
+  let patches = match args.iter().position(|a| a == "--patch") {
+    Some(n) if n + 1 < args.len() => {
+      let path = args.drain(n..n + 2).nth(1).unwrap();
+      Patch::load(&path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e))
+    }
+    _ => vec![],
+  };
+  let load = |path: &str| load(path).patched(patches.clone());
+  let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+  match args.as_slice() {
+    ["dis", path] => {
+      let program = load(path);
+      disassemble(&program.original_codes)
+        .iter()
+        .for_each(|line| println!("{}", line));
+    }
+    ["cfg", path] => {
+      let program = load(path);
+      print!(
+        "{}",
+        Cfg::new(&program.original_codes).dot(&program.original_codes)
+      );
+    }
+    ["aot", path, inputs @ ..] => aot(load(path), inputs),
+    ["symbolic", path, inputs] => {
+      let program = load(path);
+      let inputs = inputs.parse().expect("the number of inputs");
+      for path in Symbolic::new(&program.original_codes, inputs).run() {
+        println!("{}", path);
+      }
+    }
+    ["asm", path] => match assemble(&read(path)) {
+      Ok(codes) => println!(
+        "{}",
+        codes
+          .iter()
+          .map(|c| c.to_string())
+          .collect::<Vec<_>>()
+          .join(",")
+      ),
+      Err(e) => {
+        eprintln!("{}: {}", path, e);
+        std::process::exit(1);
+      }
+    },
+    ["diff", path, "--run", inputs @ ..] => {
+      let mut program = load(path);
+      let before = program.codes.clone();
+      program
+        .system
+        .input
+        .extend(inputs.iter().map(|i| i.parse::<I>().unwrap()));
+      if let Err(e) = program.run() {
+        println!("{}", e);
+      }
+      print!("{}", Diff::new(&before, &program.codes));
+    }
+    ["diff", old, new] => {
+      let memory = |path: &str| match read(path).parse::<Snapshot>() {
+        Ok(snapshot) => snapshot.memory(),
+        Err(_) => load(path).codes,
+      };
+      print!("{}", Diff::new(&memory(old), &memory(new)));
+    }
+    ["debug", path] => debug(load(path), false),
+    ["debug", path, "--ascii"] => debug(load(path), true),
+    ["profile", path, inputs @ ..] => profile(load(path), inputs),
+    ["coverage", path, scripts @ ..] => coverage(load(path), scripts),
+    ["record", path, transcript] => record(load(path), transcript),
+    ["replay", path, transcript] => replay(load(path), transcript),
+    _ => {
+      eprintln!("usage: intcode dis <program>");
+      eprintln!("       intcode cfg <program>");
+      eprintln!("       intcode aot <program> [input]...");
+      eprintln!("       intcode symbolic <program> <number of inputs>");
+      eprintln!("       intcode asm <source>");
+      eprintln!("       intcode diff <program or snapshot> <program or snapshot>");
+      eprintln!("       intcode diff <program> --run [input]...");
+      eprintln!("       intcode debug <program> [--ascii]");
+      eprintln!("       intcode profile <program> [input]...");
+      eprintln!("       intcode coverage <program> [script]...");
+      eprintln!("       intcode record <program> <transcript>");
+      eprintln!("       intcode replay <program> <transcript>");
+      eprintln!("every command takes --patch <file> to patch programs as they load");
+      std::process::exit(1);
+    }
+  }
+}
+
+fn read(path: &str) -> String {
+  fs::read_to_string(path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e))
+}
+
+/// Loads a program file, which may be annotated with `;` comments.
+fn load(path: &str) -> Program {
+  match parse_annotated(&read(path)) {
+    Ok(codes) => codes.into(),
+    Err(e) => {
+      eprintln!("{}: {}", path, e);
+      std::process::exit(1);
+    }
+  }
+}
+
+fn profile(program: Program, inputs: &[&str]) {
+  let mut program = program.profiled();
+  program
+    .system
+    .input
+    .extend(inputs.iter().map(|i| i.parse::<I>().unwrap()));
+  if let Err(e) = program.run() {
+    println!("{}", e);
+  }
+  println!("output: {:?}", program.system.output);
+  let profile = program.profile.unwrap();
+  print!("{}", profile.report(&program.original_codes, 20));
+}
+
+/// Runs an ASCII program once per script, feeding it the script's text, and reports the code
+/// and data all the runs touched together.
+fn coverage(program: Program, scripts: &[&str]) {
+  let mut program = program
+    .covered()
+    .limited(Limits::default().instructions(100_000_000));
+  for script in scripts {
+    program.reset();
+    program
+      .system
+      .input
+      .extend(read(script).bytes().map(|b| b as I));
+    match program.run() {
+      Ok(_) => println!("{}: {} outputs", script, program.system.output.len()),
+      Err(e) => println!("{}: {}", script, e),
+    }
+  }
+  if scripts.is_empty() {
+    if let Err(e) = program.run() {
+      println!("{}", e);
+    }
+  }
+  let coverage = program.coverage.unwrap();
+  print!("{}", coverage.report(&program.original_codes));
+}
+
+/// Translates a program to Rust. It first runs on `inputs` until it halts or wants more, and
+/// every jump target taken on the way becomes an entry point, so code only reached through
+/// jump tables and pointers gets translated too.
+fn aot(program: Program, inputs: &[&str]) {
+  let mut program = program.limited(Limits::default().instructions(10_000_000));
+  program
+    .system
+    .input
+    .extend(inputs.iter().map(|i| i.parse::<I>().unwrap()));
+  let mut entries = vec![];
+  loop {
+    let position = program.position;
+    let jump = matches!(
+      program.opcode(),
+      Ok(OpCode::JumpIfTrue(_, _)) | Ok(OpCode::JumpIfFalse(_, _))
+    );
+    match program.step() {
+      Ok(Some(Event::AskForInputAgain)) | Ok(Some(Event::Halted)) | Err(_) => break,
+      _ => {}
+    }
+    if jump && program.position != position + 3 {
+      entries.push(program.position);
+    }
+  }
+  entries.sort_unstable();
+  entries.dedup();
+  print!("{}", translate(&program.original_codes, &entries));
+}
+
+/// Runs an ASCII program on lines from stdin and saves everything it read and wrote.
+fn record(program: Program, path: &str) {
+  let mut program = program.recorded();
+  let stdin = io::stdin();
+  loop {
+    let event = program.run_to_event();
+    for output in program.system.system.output.drain(..) {
+      match output {
+        0..=127 => print!("{}", output as u8 as char),
+        _ => println!("{}", output),
+      }
+    }
+    match event {
+      Ok(Event::AskForInputAgain) => {
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+          break;
+        }
+        let input = &mut program.system.system.input;
+        input.extend(line.trim_end().bytes().map(|b| b as I));
+        input.push_back(10);
+      }
+      Ok(Event::Halted) => break,
+      Ok(_) => {}
+      Err(e) => {
+        println!("{}", e);
+        break;
+      }
+    }
+  }
+  let transcript = &program.system.transcript;
+  transcript
+    .save(path)
+    .unwrap_or_else(|e| panic!("Unable to write {}: {}", path, e));
+  eprintln!("recorded {} entries to {}", transcript.entries.len(), path);
+}
+
+fn replay(program: Program, path: &str) {
+  let transcript =
+    Transcript::load(path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e));
+  match transcript.replay(program) {
+    Ok(()) => println!("replayed {} entries", transcript.entries.len()),
+    Err(e) => {
+      println!("{}", e);
+      std::process::exit(1);
+    }
+  }
+}
+
+static DEBUG_HELP: &str = "commands:
+  s [n]         step n instructions
+  u [n]         step back n instructions
+  g <count>     go back to when count instructions had executed
+  l <address>   go back to just before the last write to address
+  c             continue until a breakpoint, watchpoint, input or halt
+  b <address>   toggle a breakpoint
+  w <address>   toggle a watchpoint
+  p             print position, relative base and the next instruction
+  x <address> [n]  examine n words of memory
+  i <value>...  queue input values
+  t <text>      queue text as ASCII followed by a newline
+  q             quit";
+
+fn debug(program: Program, ascii: bool) {
+  let mut debugger = Debugger::new(program);
+  println!("{}", debugger);
+  let stdin = io::stdin();
+  loop {
+    print!("> ");
+    io::stdout().flush().unwrap();
+    let mut line = String::new();
+    if stdin.lock().read_line(&mut line).unwrap() == 0 {
+      break;
+    }
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or("");
+    let numbers = words
+      .filter_map(|w| w.parse::<I>().ok())
+      .collect::<Vec<_>>();
+    let mut stop = None;
+    let back = |ok: bool| {
+      if !ok {
+        println!("the history does not go back that far");
+      }
+    };
+    match (command, numbers.as_slice()) {
+      ("g" | "l" | "b" | "w" | "x", _) if numbers.iter().any(|n| *n < 0) => {
+        println!("addresses and step counts cannot be negative")
+      }
+      ("s", []) => stop = debugger.step(),
+      ("s", [n]) => stop = (0..*n).find_map(|_| debugger.step()),
+      ("c", []) => stop = Some(debugger.run()),
+      ("u", []) => back(debugger.step_back()),
+      ("u", [n]) => back((0..*n).all(|_| debugger.step_back())),
+      ("g", [count]) => back(debugger.rewind(*count as usize)),
+      ("l", [address]) => {
+        if !debugger.rewind_to_write(*address as usize) {
+          println!("no write to {} in the history", address);
+        }
+      }
+      ("b", [address]) => toggle(&mut debugger.breakpoints, *address as usize),
+      ("w", [address]) => toggle(&mut debugger.watchpoints, *address as usize),
+      ("p", []) => println!("{}", debugger),
+      ("x", [address]) => println!("{}: {}", address, debugger.read(*address as usize)),
+      ("x", [address, n]) => {
+        (*address..*address + *n).for_each(|a| println!("{}: {}", a, debugger.read(a as usize)))
+      }
+      ("i", values) if !values.is_empty() => debugger.program.system.input.extend(values),
+      ("t", _) => {
+        let text = line.trim_start()[1..].trim();
+        let input = &mut debugger.program.system.input;
+        input.extend(text.bytes().map(|b| b as I));
+        input.push_back(10);
+      }
+      ("q", []) => break,
+      _ => println!("{}", DEBUG_HELP),
+    }
+    if let "u" | "g" | "l" = command {
+      println!("{}", debugger);
+    }
+    for output in debugger.program.system.output.drain(..) {
+      if ascii && (0..128).contains(&output) {
+        print!("{}", output as u8 as char);
+      } else {
+        println!("output: {}", output);
+      }
+    }
+    if let Some(stop) = stop {
+      println!("{}", stop);
+      println!("{}", debugger);
+    }
+  }
+}
+
+fn toggle(set: &mut HashSet<usize>, address: usize) {
+  if !set.remove(&address) {
+    set.insert(address);
+  }
+}
@@ -0,0 +1,326 @@
+use super::{OpCode, ParameterMode, Word, I};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssembleError {
+  pub line: usize,
+  pub message: String,
+}
+
+impl Display for AssembleError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    write!(f, "line {}: {}", self.line, self.message)
+  }
+}
+
+impl<W: Word> OpCode<W> {
+  pub fn code(&self) -> I {
+    match self {
+      Self::Add(_, _, _) => 1,
+      Self::Multiply(_, _, _) => 2,
+      Self::Input(_) => 3,
+      Self::Output(_) => 4,
+      Self::JumpIfTrue(_, _) => 5,
+      Self::JumpIfFalse(_, _) => 6,
+      Self::LessThan(_, _, _) => 7,
+      Self::Equals(_, _, _) => 8,
+      Self::AdjustRelativeBase(_) => 9,
+      Self::Break => 99,
+      Self::Custom { code, .. } => *code,
+    }
+  }
+  /// The words `OpCode::decode` turns back into this instruction.
+  pub fn encode(&self) -> Vec<W> {
+    let parameters = self.parameters();
+    let modes = parameters
+      .iter()
+      .zip(&[100, 1000, 10000])
+      .map(|(p, m)| p.mode() * m)
+      .sum::<I>();
+    let mut words = vec![W::from_i64(self.code() + modes)];
+    words.extend(parameters.iter().map(|p| p.word()));
+    words
+  }
+  fn from_mnemonic(mnemonic: &str, p: &[ParameterMode<W>]) -> Option<Self> {
+    let q = |n: usize| p[n].clone();
+    let opcode = match (mnemonic, p.len()) {
+      ("add", 3) => Self::Add(q(0), q(1), q(2)),
+      ("mul", 3) => Self::Multiply(q(0), q(1), q(2)),
+      ("in", 1) => Self::Input(q(0)),
+      ("out", 1) => Self::Output(q(0)),
+      ("jt", 2) => Self::JumpIfTrue(q(0), q(1)),
+      ("jf", 2) => Self::JumpIfFalse(q(0), q(1)),
+      ("lt", 3) => Self::LessThan(q(0), q(1), q(2)),
+      ("eq", 3) => Self::Equals(q(0), q(1), q(2)),
+      ("arb", 1) => Self::AdjustRelativeBase(q(0)),
+      ("hlt", 0) => Self::Break,
+      // Extensions, such as `op42`. Which parameter is an output only matters when decoding.
+      (custom, n) if n <= 3 => Self::Custom {
+        code: custom
+          .strip_prefix("op")?
+          .parse()
+          .ok()
+          .filter(|code| (10..99).contains(code))?,
+        inputs: [p.first().cloned(), p.get(1).cloned(), p.get(2).cloned()],
+        output: None,
+      },
+      _ => return None,
+    };
+    Some(opcode)
+  }
+  /// The parameter written to, if any.
+  pub fn destination(&self) -> Option<ParameterMode<W>> {
+    match self {
+      Self::Add(_, _, p3) | Self::Multiply(_, _, p3) => Some(p3.clone()),
+      Self::LessThan(_, _, p3) | Self::Equals(_, _, p3) => Some(p3.clone()),
+      Self::Input(p1) => Some(p1.clone()),
+      Self::Custom { output, .. } => output.clone(),
+      _ => None,
+    }
+  }
+}
+
+struct Statement<'a> {
+  line: usize,
+  mnemonic: &'a str,
+  operands: Vec<&'a str>,
+}
+
+/// Assembles the listing syntax produced by `disassemble` back into program words.
+///
+/// Each line is an optional `label:` followed by a mnemonic and its comma separated operands:
+/// `[x]` for position, `#x` for immediate and `rb+x` for relative mode, where `x` is a number, a
+/// label or `label+n`. `.data` emits its operands as raw words and `;` starts a comment.
+pub fn assemble(source: &str) -> Result<Vec<I>, AssembleError> {
+  let mut labels = HashMap::new();
+  let mut statements = Vec::new();
+  let mut address = 0;
+  for (n, line) in source.lines().enumerate() {
+    let line_number = n + 1;
+    let err = |message: String| AssembleError {
+      line: line_number,
+      message,
+    };
+    let mut rest = line.split(';').next().unwrap().trim();
+    while let Some(colon) = rest.find(':') {
+      let label = rest[..colon].trim();
+      if !is_label(label) {
+        return Err(err(format!("invalid label '{}'", label)));
+      }
+      if labels.insert(label, address).is_some() {
+        return Err(err(format!("label '{}' defined twice", label)));
+      }
+      rest = rest[colon + 1..].trim();
+    }
+    if rest.is_empty() {
+      continue;
+    }
+    let (mnemonic, operands) = match rest.find(char::is_whitespace) {
+      Some(i) => (&rest[..i], rest[i..].split(',').map(str::trim).collect()),
+      None => (rest, vec![]),
+    };
+    address += match mnemonic {
+      ".data" => operands.len(),
+      _ => operands.len() + 1,
+    };
+    statements.push(Statement {
+      line: line_number,
+      mnemonic,
+      operands,
+    });
+  }
+
+  let mut codes = Vec::with_capacity(address);
+  for statement in statements {
+    let err = |message: String| AssembleError {
+      line: statement.line,
+      message,
+    };
+    let value = |s: &str| resolve(s, &labels).map_err(err);
+    if statement.mnemonic == ".data" {
+      for operand in &statement.operands {
+        codes.push(value(operand)?);
+      }
+      continue;
+    }
+    let parameters = statement
+      .operands
+      .iter()
+      .map(|operand| {
+        if let Some(inner) = operand.strip_prefix('[') {
+          match inner.strip_suffix(']') {
+            Some(inner) => value(inner).map(ParameterMode::Position),
+            None => Err(err(format!("missing ']' in '{}'", operand))),
+          }
+        } else if let Some(immediate) = operand.strip_prefix('#') {
+          value(immediate).map(ParameterMode::Immediate)
+        } else if let Some(offset) = operand.strip_prefix("rb") {
+          let offset = offset.trim();
+          match offset.chars().next() {
+            None => Ok(ParameterMode::Relative(0)),
+            Some('+') => value(&offset[1..]).map(ParameterMode::Relative),
+            Some('-') => value(&offset[1..]).and_then(|i| match i.checked_neg() {
+              Some(i) => Ok(ParameterMode::Relative(i)),
+              None => Err(err(format!("relative operand '{}' out of range", operand))),
+            }),
+            _ => Err(err(format!("invalid relative operand '{}'", operand))),
+          }
+        } else {
+          Err(err(format!("operand '{}' needs a mode", operand)))
+        }
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let opcode = OpCode::<I>::from_mnemonic(statement.mnemonic, &parameters).ok_or_else(|| {
+      err(format!(
+        "unknown instruction '{}' with {} operands",
+        statement.mnemonic,
+        parameters.len()
+      ))
+    })?;
+    if let Some(ParameterMode::Immediate(_)) = opcode.destination() {
+      return Err(err(format!("'{}' cannot write to an immediate", opcode)));
+    }
+    codes.extend(opcode.encode());
+  }
+  Ok(codes)
+}
+
+fn is_label(s: &str) -> bool {
+  let mut chars = s.chars();
+  chars
+    .next()
+    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Resolves a number, `label` or `label+n`/`label-n` to a value.
+fn resolve(s: &str, labels: &HashMap<&str, usize>) -> Result<I, String> {
+  let s = s.trim();
+  if let Ok(i) = s.parse::<I>() {
+    return Ok(i);
+  }
+  let (label, offset) = match s.find(['+', '-']) {
+    Some(i) => {
+      let offset = s[i + 1..]
+        .trim()
+        .parse::<I>()
+        .map_err(|_| format!("invalid offset in '{}'", s))?;
+      let offset = if &s[i..=i] == "-" {
+        offset.checked_neg()
+      } else {
+        Some(offset)
+      };
+      (s[..i].trim(), offset)
+    }
+    None => (s, Some(0)),
+  };
+  let address = labels
+    .get(label)
+    .ok_or_else(|| format!("unknown label '{}'", label))?;
+  offset
+    .and_then(|offset| I::try_from(*address).ok()?.checked_add(offset))
+    .ok_or_else(|| format!("'{}' is out of range", s))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::{disassemble, Program};
+
+  fn run(codes: Vec<I>, input: &[I]) -> Vec<I> {
+    let mut program: Program = codes.into();
+    program.system.input.extend(input);
+    program.run().unwrap();
+    program.system.output
+  }
+
+  #[test]
+  fn test_assemble() {
+    let source = "
+      ; doubles the input, counting down to zero
+      start: in [n]
+      loop:  mul [n], #2, [n]
+             out [n]
+             add [count], #-1, [count]
+             jt [count], #loop
+             hlt
+      n:     .data 0
+      count: .data 3
+    ";
+    let codes = assemble(source).unwrap();
+    assert_eq!(
+      codes,
+      [3, 16, 1002, 16, 2, 16, 4, 16, 1001, 17, -1, 17, 1005, 17, 2, 99, 0, 3]
+    );
+    assert_eq!(run(codes, &[5]), [10, 20, 40]);
+  }
+
+  #[test]
+  fn test_relative_base() {
+    let source = "
+      arb #buffer
+      in rb+1
+      add rb+1, #1, rb+0
+      arb #1
+      out rb-1
+      hlt
+      buffer: .data 0, 0
+    ";
+    assert_eq!(run(assemble(source).unwrap(), &[41]), [42]);
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let codes = vec![
+      109, 19, 21101, 3, 4, 0, 1206, -1, 12, 204, 1, 99, 2, 17, 18, 19, 99,
+    ];
+    let listing = disassemble(&codes)
+      .iter()
+      .map(|line| match &line.opcode {
+        Some(opcode) => opcode.to_string(),
+        None => format!(".data {}", line.words[0]),
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+    assert_eq!(assemble(&listing).unwrap(), codes);
+  }
+
+  #[test]
+  fn test_errors() {
+    assert_eq!(
+      assemble("add #1, #2, #3").unwrap_err(),
+      AssembleError {
+        line: 1,
+        message: "'add #1, #2, #3' cannot write to an immediate".into()
+      }
+    );
+    assert_eq!(
+      assemble("\njt #1, #nowhere").unwrap_err().message,
+      "unknown label 'nowhere'"
+    );
+    assert!(assemble("mul [1], #2").is_err());
+    // Operands that do not fit in a word are errors rather than overflows.
+    assert_eq!(
+      assemble("out rb--9223372036854775808").unwrap_err().message,
+      "relative operand 'rb--9223372036854775808' out of range"
+    );
+    assert_eq!(
+      assemble("out #0\nend: out [end+9223372036854775807]")
+        .unwrap_err()
+        .message,
+      "'end+9223372036854775807' is out of range"
+    );
+    assert_eq!(
+      assemble("x: .data x--9223372036854775808")
+        .unwrap_err()
+        .message,
+      "'x--9223372036854775808' is out of range"
+    );
+    assert_eq!(
+      assemble("x: .data x-9223372036854775807"),
+      Ok(vec![-9223372036854775807])
+    );
+  }
+}
@@ -0,0 +1,114 @@
+use super::{IntcodeError, Memory, Program, Queue, System, Word};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Runs one program over many independent sets of inputs on a pool of worker threads. Every
+/// run starts from a copy-on-write clone of the original image, so a run only copies the pages
+/// it writes to.
+#[derive(Clone, Debug)]
+pub struct Batch<S: System> {
+  program: Program<S>,
+  image: Memory<S::Word>,
+  threads: usize,
+}
+
+impl<S: System + Clone + Sync> Batch<S> {
+  /// Runs `program` on as many threads as there are cores.
+  pub fn new(mut program: Program<S>) -> Self {
+    // A run's results are collected after it ends, so it must not reset itself first.
+    program.reset_after_run = false;
+    let image = program.image();
+    Self {
+      program,
+      image,
+      threads: thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+  }
+  pub fn threads(mut self, threads: usize) -> Self {
+    self.threads = threads.max(1);
+    self
+  }
+  /// Calls `f` with a freshly reset program for each of `inputs`, returning the results in the
+  /// same order as the inputs.
+  pub fn map<In: Sync, T: Send>(
+    &self,
+    inputs: &[In],
+    f: impl Fn(&mut Program<S>, &In) -> T + Sync,
+  ) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let worker = || {
+      let mut program = self.program.clone();
+      let mut results = vec![];
+      loop {
+        let n = next.fetch_add(1, Ordering::Relaxed);
+        let input = match inputs.get(n) {
+          Some(input) => input,
+          None => return results,
+        };
+        program.restart(self.image.clone());
+        results.push((n, f(&mut program, input)));
+      }
+    };
+    let mut results = thread::scope(|scope| {
+      let workers = (0..self.threads.min(inputs.len()))
+        .map(|_| scope.spawn(worker))
+        .collect::<Vec<_>>();
+      workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect::<Vec<_>>()
+    });
+    results.sort_unstable_by_key(|(n, _)| *n);
+    results.into_iter().map(|(_, result)| result).collect()
+  }
+}
+
+impl<W: Word> Batch<Queue<W>> {
+  /// Runs the program on each set of inputs until it halts or wants more, returning every
+  /// output of each run.
+  pub fn run(&self, inputs: &[Vec<W>]) -> Vec<Result<Vec<W>, IntcodeError<W>>> {
+    self.map(inputs, |program, inputs| {
+      program.system.input.extend(inputs.iter().cloned());
+      program.run()?;
+      Ok(std::mem::take(&mut program.system.output))
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_batch() {
+    // Outputs the sum of two inputs, after storing it over the program's own first word.
+    let program = "3,11,3,12,1,11,12,0,4,0,99,0,0".parse::<Program>().unwrap();
+    let inputs = (0..100).map(|i| vec![i, 2 * i]).collect::<Vec<_>>();
+    let expected = (0..100).map(|i| Ok(vec![3 * i])).collect::<Vec<_>>();
+    assert_eq!(
+      Batch::new(program.clone()).threads(4).run(&inputs),
+      expected
+    );
+    assert_eq!(
+      Batch::new(program.clone()).threads(1).run(&inputs),
+      expected
+    );
+    // The pristine image is never written to.
+    assert_eq!(program.codes.get(0), 3);
+
+    let lengths = Batch::new(program).map(&["a", "bcd", ""], |_, s| s.len());
+    assert_eq!(lengths, [1, 3, 0]);
+    // Waiting for input ends a run as well as halting does.
+    let program = "3,0,4,0,42".parse::<Program>().unwrap();
+    assert_eq!(
+      Batch::new(program).run(&[vec![], vec![5]]),
+      [
+        Ok(vec![]),
+        Err(IntcodeError::InvalidOpCode {
+          address: 4,
+          code: 42
+        })
+      ]
+    );
+  }
+}
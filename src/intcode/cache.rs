@@ -0,0 +1,158 @@
+use super::{OpCode, Word, I};
+use std::collections::{HashMap, HashSet};
+
+/// Entries per page. Smaller than a memory page, since an entry is several words.
+const PAGE_SIZE: usize = 256;
+/// Pages below this index live in a directly indexed table, the rest in a hash map.
+const NEAR_PAGES: usize = 1 << 14;
+
+type Page<W> = Box<[Option<OpCode<W>>]>;
+
+/// Decoded instructions by address, so hot loops skip the opcode and mode arithmetic. Entries
+/// are kept in pages like `Memory`'s, so code far out in memory only costs the pages it is on.
+///
+/// `Program` drops the entries covering every address it writes to, and on a reset only checks
+/// the pages it wrote to since the last one. Writes made straight to `Program::codes` go around
+/// it, so `clear` the cache after patching code by hand.
+#[derive(Clone, Debug, Default)]
+pub struct DecodeCache<W = I> {
+  near: Vec<Option<Page<W>>>,
+  far: HashMap<usize, Page<W>>,
+  /// Pages holding entries that may overlap a word written since the last `retain_written`.
+  written: HashSet<usize>,
+  /// The page last added to `written`. Writes cluster, so this skips most of the hashing.
+  last_written: Option<usize>,
+  pub hits: usize,
+  pub misses: usize,
+}
+
+impl<W: Word> DecodeCache<W> {
+  pub fn get(&mut self, address: usize) -> Option<OpCode<W>> {
+    let opcode = self
+      .page(address / PAGE_SIZE)
+      .and_then(|page| page[address % PAGE_SIZE].clone());
+    match opcode {
+      Some(_) => self.hits += 1,
+      None => self.misses += 1,
+    }
+    opcode
+  }
+  pub fn insert(&mut self, address: usize, opcode: OpCode<W>) {
+    self.page_mut(address / PAGE_SIZE)[address % PAGE_SIZE] = Some(opcode);
+  }
+  /// Forgets every instruction that `address` could be part of.
+  pub fn invalidate(&mut self, address: usize) {
+    for n in [address.saturating_sub(3) / PAGE_SIZE, address / PAGE_SIZE] {
+      if self.last_written != Some(n) {
+        self.written.insert(n);
+        self.last_written = Some(n);
+      }
+    }
+    for address in address.saturating_sub(3)..=address {
+      if let Some(page) = self.page_mut_if_present(address / PAGE_SIZE) {
+        page[address % PAGE_SIZE] = None;
+      }
+    }
+  }
+  /// Keeps only the entries that `keep` returns true for, given their address.
+  pub fn retain(&mut self, mut keep: impl FnMut(usize, &OpCode<W>) -> bool) {
+    let near = self.near.iter_mut().enumerate();
+    let pages = near
+      .filter_map(|(n, page)| Some((n, page.as_mut()?)))
+      .chain(self.far.iter_mut().map(|(n, page)| (*n, page)));
+    for (n, page) in pages {
+      for (i, entry) in page.iter_mut().enumerate() {
+        if matches!(entry, Some(opcode) if !keep(n * PAGE_SIZE + i, opcode)) {
+          *entry = None;
+        }
+      }
+    }
+  }
+  /// Like `retain`, but only visits the pages `invalidate` was called on since the last call,
+  /// which are the only ones holding entries decoded from a written word.
+  pub fn retain_written(&mut self, mut keep: impl FnMut(usize, &OpCode<W>) -> bool) {
+    self.last_written = None;
+    for n in std::mem::take(&mut self.written) {
+      if let Some(page) = self.page_mut_if_present(n) {
+        for (i, entry) in page.iter_mut().enumerate() {
+          if matches!(entry, Some(opcode) if !keep(n * PAGE_SIZE + i, opcode)) {
+            *entry = None;
+          }
+        }
+      }
+    }
+  }
+  pub fn is_empty(&self) -> bool {
+    self.near.iter().all(Option::is_none) && self.far.is_empty()
+  }
+  pub fn clear(&mut self) {
+    self.near.clear();
+    self.far.clear();
+    self.written.clear();
+    self.last_written = None;
+  }
+  fn page(&self, n: usize) -> Option<&Page<W>> {
+    if n < NEAR_PAGES {
+      self.near.get(n)?.as_ref()
+    } else {
+      self.far.get(&n)
+    }
+  }
+  fn page_mut_if_present(&mut self, n: usize) -> Option<&mut Page<W>> {
+    if n < NEAR_PAGES {
+      self.near.get_mut(n)?.as_mut()
+    } else {
+      self.far.get_mut(&n)
+    }
+  }
+  fn page_mut(&mut self, n: usize) -> &mut Page<W> {
+    let new_page = || vec![None; PAGE_SIZE].into_boxed_slice();
+    if n < NEAR_PAGES {
+      if self.near.len() <= n {
+        self.near.resize_with(n + 1, || None);
+      }
+      self.near[n].get_or_insert_with(new_page)
+    } else {
+      self.far.entry(n).or_insert_with(new_page)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::ParameterMode;
+
+  #[test]
+  fn test_pages() {
+    let mut cache = DecodeCache::default();
+    let far = 1 << 40;
+    cache.insert(far, OpCode::<I>::Output(ParameterMode::Immediate(1)));
+    cache.insert(5, OpCode::Break);
+    assert_eq!(cache.far.len(), 1);
+    assert_eq!(cache.near.len(), 1);
+    assert_eq!(
+      cache.get(far),
+      Some(OpCode::Output(ParameterMode::Immediate(1)))
+    );
+    cache.invalidate(far + 2);
+    assert_eq!(cache.get(far), None);
+    cache.retain(|address, _| address != 5);
+    assert!(cache.get(5).is_none());
+    assert_eq!((cache.hits, cache.misses), (1, 2));
+
+    // Only entries on pages written to since the last call are visited.
+    cache.insert(5, OpCode::Break);
+    cache.insert(far + PAGE_SIZE, OpCode::Break);
+    cache.invalidate(far + 3);
+    cache.insert(far + 3, OpCode::Break);
+    let mut visited = vec![];
+    cache.retain_written(|address, _| {
+      visited.push(address);
+      true
+    });
+    assert_eq!(visited, [far + 3]);
+    cache.retain_written(|_, _| false);
+    assert!(cache.get(far + 3).is_some());
+  }
+}
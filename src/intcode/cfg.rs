@@ -0,0 +1,275 @@
+use super::{Line, OpCode, ParameterMode, Word};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// How control leaves a basic block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Exit {
+  /// Runs on into the block starting at the address.
+  Next(usize),
+  Jump(usize),
+  /// A conditional jump to `taken` that otherwise runs on to `next`.
+  Branch {
+    taken: usize,
+    next: usize,
+  },
+  /// An unconditional jump right after storing the address that follows it, which is how
+  /// compiled Intcode calls a function. `target` is `None` for calls through a pointer.
+  Call {
+    target: Option<usize>,
+    ret: usize,
+  },
+  /// A jump whose target is only known at run time, such as a function return. `next` is set
+  /// when the jump is conditional.
+  Indirect {
+    next: Option<usize>,
+  },
+  Halt,
+  /// The block runs into something that does not decode.
+  Invalid,
+}
+
+impl Exit {
+  pub fn successors(&self) -> Vec<usize> {
+    match *self {
+      Self::Next(a) | Self::Jump(a) => vec![a],
+      Self::Branch { taken, next } => vec![taken, next],
+      Self::Call { target, ret } => target.into_iter().chain(Some(ret)).collect(),
+      Self::Indirect { next } => next.into_iter().collect(),
+      Self::Halt | Self::Invalid => vec![],
+    }
+  }
+}
+
+/// Instructions from `start` up to `end` that run straight through to `exit`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+  pub start: usize,
+  pub end: usize,
+  pub exit: Exit,
+}
+
+impl Block {
+  pub fn lines<'a, W: Word>(&self, codes: &'a [W]) -> Vec<Line<'a, W>> {
+    let mut lines = vec![];
+    let mut address = self.start;
+    while address < self.end {
+      let line = Line::at(codes, address);
+      address += line.words.len();
+      lines.push(line);
+    }
+    lines
+  }
+}
+
+/// The basic blocks reachable from a program's entry points, found by following every jump
+/// with an immediate target. Code only reached through indirect jumps, other than the return
+/// site of a call, is left out unless it is given as an entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cfg {
+  pub blocks: BTreeMap<usize, Block>,
+}
+
+impl Cfg {
+  pub fn new<W: Word>(codes: &[W]) -> Self {
+    Self::with_entries(codes, &[0])
+  }
+  pub fn with_entries<W: Word>(codes: &[W], entries: &[usize]) -> Self {
+    let mut leaders = entries.iter().copied().collect::<BTreeSet<_>>();
+    // Every new leader can split a block found earlier, so rebuild until nothing new turns up.
+    loop {
+      let blocks = leaders
+        .iter()
+        .map(|&start| (start, block(codes, start, &leaders)))
+        .collect::<BTreeMap<_, _>>();
+      let found = blocks
+        .values()
+        .flat_map(|b| b.exit.successors())
+        .filter(|a| !leaders.contains(a))
+        .collect::<Vec<_>>();
+      if found.is_empty() {
+        return Self { blocks };
+      }
+      leaders.extend(found);
+    }
+  }
+  /// The graph in Graphviz DOT format, with each block's disassembly as its label. Blocks
+  /// ending in an indirect jump are drawn in red.
+  pub fn dot<W: Word>(&self, codes: &[W]) -> String {
+    let mut dot = String::new();
+    writeln!(dot, "digraph intcode {{").unwrap();
+    writeln!(dot, "  node [shape=box, fontname=\"monospace\"];").unwrap();
+    for block in self.blocks.values() {
+      let mut label = block
+        .lines(codes)
+        .iter()
+        .map(|line| match &line.opcode {
+          Some(opcode) => format!("{}: {}\\l", line.address, opcode),
+          None => format!("{}: .data {}\\l", line.address, line.words[0]),
+        })
+        .collect::<String>();
+      let mut attributes = String::new();
+      match block.exit {
+        Exit::Indirect { .. } | Exit::Call { target: None, .. } => {
+          label.push_str("-> ?\\l");
+          attributes.push_str(", color=red");
+        }
+        Exit::Invalid => label.push_str(&format!("{}: invalid\\l", block.end)),
+        _ => {}
+      }
+      writeln!(
+        dot,
+        "  b{} [label=\"{}\"{}];",
+        block.start,
+        label.replace('"', "\\\""),
+        attributes
+      )
+      .unwrap();
+      let mut edge = |to: usize, attributes: &str| {
+        writeln!(dot, "  b{} -> b{}{};", block.start, to, attributes).unwrap()
+      };
+      match block.exit {
+        Exit::Next(to) | Exit::Jump(to) => edge(to, ""),
+        Exit::Branch { taken, next } => {
+          edge(taken, " [label=\"taken\"]");
+          edge(next, " [label=\"not taken\"]");
+        }
+        Exit::Call { target, ret } => {
+          if let Some(target) = target {
+            edge(target, " [label=\"call\"]");
+          }
+          edge(ret, " [label=\"return\", style=dashed]");
+        }
+        Exit::Indirect { next: Some(next) } => edge(next, " [label=\"not taken\"]"),
+        Exit::Indirect { next: None } | Exit::Halt | Exit::Invalid => {}
+      }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+  }
+}
+
+fn block<W: Word>(codes: &[W], start: usize, leaders: &BTreeSet<usize>) -> Block {
+  let mut address = start;
+  let mut previous = None;
+  loop {
+    let opcode = match OpCode::decode(codes, address) {
+      Ok(opcode) if address + opcode.size() <= codes.len() => opcode,
+      _ => {
+        return Block {
+          start,
+          end: address,
+          exit: Exit::Invalid,
+        }
+      }
+    };
+    let next = address + opcode.size();
+    let exit = match terminator(&opcode, previous.as_ref(), next) {
+      Some(exit) => exit,
+      None if leaders.contains(&next) => Exit::Next(next),
+      None => {
+        previous = Some(opcode);
+        address = next;
+        continue;
+      }
+    };
+    return Block {
+      start,
+      end: next,
+      exit,
+    };
+  }
+}
+
+/// How `opcode` ends a block, or `None` if execution always carries on to `next`.
+fn terminator<W: Word>(
+  opcode: &OpCode<W>,
+  previous: Option<&OpCode<W>>,
+  next: usize,
+) -> Option<Exit> {
+  let (condition, target, jump_if_zero) = match opcode {
+    OpCode::Break => return Some(Exit::Halt),
+    OpCode::JumpIfTrue(condition, target) => (condition, target, false),
+    OpCode::JumpIfFalse(condition, target) => (condition, target, true),
+    _ => return None,
+  };
+  let target = match target {
+    ParameterMode::Immediate(t) => t.to_i64().filter(|t| *t >= 0).map(|t| t as usize),
+    _ => None,
+  };
+  let always = match condition {
+    // A jump on a constant either always happens or never does.
+    ParameterMode::Immediate(c) if c.is_zero() != jump_if_zero => return None,
+    ParameterMode::Immediate(_) => true,
+    _ => false,
+  };
+  Some(match (always, target) {
+    (false, Some(taken)) => Exit::Branch { taken, next },
+    (false, None) => Exit::Indirect { next: Some(next) },
+    (true, target) if previous.and_then(stored_constant) == Some(next) => {
+      Exit::Call { target, ret: next }
+    }
+    (true, Some(target)) => Exit::Jump(target),
+    (true, None) => Exit::Indirect { next: None },
+  })
+}
+
+/// The value an instruction stores when both its inputs are immediates.
+fn stored_constant<W: Word>(opcode: &OpCode<W>) -> Option<usize> {
+  let value = match opcode {
+    OpCode::Add(ParameterMode::Immediate(a), ParameterMode::Immediate(b), _) => a.wrapping_add(b),
+    OpCode::Multiply(ParameterMode::Immediate(a), ParameterMode::Immediate(b), _) => {
+      a.wrapping_mul(b)
+    }
+    _ => return None,
+  };
+  value.to_i64().filter(|v| *v >= 0).map(|v| v as usize)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::I;
+
+  #[test]
+  fn test_cfg() {
+    let codes: [I; 32] = [
+      3, 30, 1005, 30, 11, // in [30]; jt [30], #11
+      104, 0, 1106, 0, 20, // out #0; jf #0, #20
+      7,  // unreachable
+      1101, 18, 0, 31, 1105, 1, 21, // add #18, #0, [31]; jt #1, #21
+      104, 1, 99, // out #1; hlt
+      105, 1, 31, // jt #1, [31]
+      0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let cfg = Cfg::new(&codes);
+    let blocks = cfg.blocks.values().copied().collect::<Vec<_>>();
+    let block = |start, end, exit| Block { start, end, exit };
+    assert_eq!(
+      blocks,
+      [
+        block(0, 5, Exit::Branch { taken: 11, next: 5 }),
+        block(5, 10, Exit::Jump(20)),
+        block(
+          11,
+          18,
+          Exit::Call {
+            target: Some(21),
+            ret: 18
+          }
+        ),
+        block(18, 20, Exit::Next(20)),
+        block(20, 21, Exit::Halt),
+        block(21, 24, Exit::Indirect { next: None }),
+      ]
+    );
+
+    let dot = cfg.dot(&codes);
+    assert!(dot.starts_with("digraph intcode {\n"));
+    assert!(dot.contains("  b0 [label=\"0: in [30]\\l2: jt [30], #11\\l\"];\n"));
+    assert!(dot.contains("  b0 -> b11 [label=\"taken\"];\n"));
+    assert!(dot.contains("  b11 -> b18 [label=\"return\", style=dashed];\n"));
+    assert!(dot.contains("  b21 [label=\"21: jt #1, [31]\\l-> ?\\l\", color=red];\n"));
+    assert!(dot.ends_with("}\n"));
+  }
+}
@@ -0,0 +1,148 @@
+use super::{Line, OpCode, ParameterMode, Word};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// The addresses a program executed, read and wrote. Like `Profile` it survives
+/// `Program::reset`, so it covers every run, and coverage from other copies can be merged in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+  /// Addresses of executed instructions.
+  pub executed: BTreeSet<usize>,
+  /// Addresses read as data by a parameter.
+  pub read: BTreeSet<usize>,
+  pub written: BTreeSet<usize>,
+}
+
+impl Coverage {
+  /// Records the instruction at `address`, with its parameters resolved against the relative
+  /// base it ran with.
+  pub fn record<W: Word>(&mut self, address: usize, opcode: &OpCode<W>, relative_position: &W) {
+    self.executed.insert(address);
+    let destination = opcode.destination();
+    let to_address = |p: &ParameterMode<W>| {
+      p.address(relative_position)
+        .and_then(|a| a.to_i64())
+        .filter(|a| *a >= 0)
+        .map(|a| a as usize)
+    };
+    let parameters = opcode.parameters();
+    let (reads, writes) = match destination {
+      Some(_) => parameters.split_at(parameters.len() - 1),
+      None => (parameters.as_slice(), &[][..]),
+    };
+    self.read.extend(reads.iter().filter_map(to_address));
+    self.written.extend(writes.iter().filter_map(to_address));
+  }
+  pub fn merge(&mut self, other: &Coverage) {
+    self.executed.extend(&other.executed);
+    self.read.extend(&other.read);
+    self.written.extend(&other.written);
+  }
+  /// Forgets every address in `other`, the opposite of `merge`.
+  pub fn remove(&mut self, other: &Coverage) {
+    for (set, other) in [
+      (&mut self.executed, &other.executed),
+      (&mut self.read, &other.read),
+      (&mut self.written, &other.written),
+    ] {
+      other.iter().for_each(|a| {
+        set.remove(a);
+      });
+    }
+  }
+  /// The addresses in `self` that are not in `other`.
+  pub fn without(mut self, other: &Coverage) -> Coverage {
+    self.executed.retain(|a| !other.executed.contains(a));
+    self.read.retain(|a| !other.read.contains(a));
+    self.written.retain(|a| !other.written.contains(a));
+    self
+  }
+  /// A disassembly of `codes` with each line marked `x` if it was executed, `r` if any of its
+  /// words were read and `w` if any were written. Executed addresses always start a line, so
+  /// code the linear sweep would misalign is shown the way it ran.
+  pub fn report<W: Word>(&self, codes: &[W]) -> String {
+    let mut report = String::new();
+    writeln!(
+      report,
+      "executed: {} read: {} written: {} ({} written outside the program)",
+      self.executed.len(),
+      self.read.len(),
+      self.written.len(),
+      self.written.range(codes.len()..).count()
+    )
+    .unwrap();
+    let mut address = 0;
+    while address < codes.len() {
+      let mut line = Line::at(codes, address);
+      let end = address + line.words.len();
+      if self.executed.range(address + 1..end).next().is_some() {
+        line = Line {
+          address,
+          words: &codes[address..=address],
+          opcode: None,
+        };
+      }
+      let range = address..address + line.words.len();
+      let mark = |set: &BTreeSet<usize>, c| {
+        if set.range(range.clone()).next().is_some() {
+          c
+        } else {
+          '.'
+        }
+      };
+      let executed = if self.executed.contains(&address) {
+        'x'
+      } else {
+        '.'
+      };
+      writeln!(
+        report,
+        "{}{}{} {}",
+        executed,
+        mark(&self.read, 'r'),
+        mark(&self.written, 'w'),
+        line
+      )
+      .unwrap();
+      address = range.end;
+    }
+    report
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::{Program, I};
+
+  #[test]
+  fn test_coverage() {
+    // Echoes its input while it is not zero, at the end of a loop that is entered at 0.
+    let codes: Vec<I> = vec![3, 11, 1006, 11, 10, 4, 11, 1105, 1, 0, 99, 0];
+    let mut program = Program::from(codes).covered().i(0);
+    program.run().unwrap();
+    program.reset();
+    program.system.input.extend(&[5, 0]);
+    program.run().unwrap();
+    let coverage = program.coverage.unwrap();
+    let addresses = |set: &BTreeSet<usize>| set.iter().copied().collect::<Vec<_>>();
+    assert_eq!(addresses(&coverage.executed), [0, 2, 5, 7, 10]);
+    assert_eq!(addresses(&coverage.read), [11]);
+    assert_eq!(addresses(&coverage.written), [11]);
+
+    let mut other = Coverage::default();
+    other.executed.insert(20);
+    let mut merged = coverage.clone();
+    merged.merge(&other);
+    assert_eq!(merged.executed.len(), 6);
+
+    let report = coverage.report(&program.original_codes);
+    let lines = report.lines().collect::<Vec<_>>();
+    assert_eq!(
+      lines[0],
+      "executed: 5 read: 1 written: 1 (0 written outside the program)"
+    );
+    assert_eq!(lines[1], "x..      0: 3,11                     in [11]");
+    assert_eq!(lines[6], ".rw     11: 0                        .data 0");
+  }
+}
@@ -0,0 +1,329 @@
+use super::{Coverage, Entry, Event, IntcodeError, Line, Program, System, Word, I};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Error, Formatter};
+
+/// Why the debugger handed control back.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stop<W = I> {
+  /// The next instruction to run is at a breakpoint.
+  Breakpoint(usize),
+  /// The last instruction wrote to a watched address.
+  Watchpoint {
+    address: usize,
+    old: W,
+    new: W,
+  },
+  WaitingForInput,
+  Halted,
+  /// The next instruction cannot be executed.
+  Error(IntcodeError<W>),
+}
+
+/// What an executed instruction changed, so it can be undone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record<W = I> {
+  pub position: usize,
+  pub relative_position: W,
+  /// The address written and the value it held before.
+  pub write: Option<(usize, W)>,
+  /// The value an input or output instruction passed between the program and its system.
+  pub io: Option<Entry<W>>,
+  /// What the instruction added to the program's coverage, if it is tracked.
+  pub coverage: Option<Coverage>,
+}
+
+/// Single steps a `Program`, stopping at breakpoint addresses and on writes to watched cells.
+/// Every step is recorded so it can be stepped back over later.
+pub struct Debugger<S: System> {
+  pub program: Program<S>,
+  pub breakpoints: HashSet<usize>,
+  pub watchpoints: HashSet<usize>,
+  pub steps: usize,
+  /// The most recent steps, oldest first.
+  pub history: VecDeque<Record<S::Word>>,
+  /// How many steps `history` keeps before it forgets the oldest.
+  pub history_limit: usize,
+}
+
+impl<S: System> Debugger<S> {
+  pub fn new(program: Program<S>) -> Self {
+    Self {
+      program,
+      breakpoints: HashSet::new(),
+      watchpoints: HashSet::new(),
+      steps: 0,
+      history: VecDeque::new(),
+      history_limit: 1_000_000,
+    }
+  }
+  /// Executes one instruction, returning a `Stop` if it triggered one.
+  pub fn step(&mut self) -> Option<Stop<S::Word>> {
+    let opcode = self.program.opcode().ok();
+    let written = opcode
+      .as_ref()
+      .and_then(|opcode| opcode.destination())
+      .and_then(|p| self.program.destination(&p).ok());
+    let old = written.map(|address| self.read(address));
+    let coverage = self
+      .program
+      .coverage
+      .as_ref()
+      .zip(opcode)
+      .map(|(coverage, opcode)| {
+        let mut step = Coverage::default();
+        step.record(
+          self.program.position,
+          &opcode,
+          &self.program.relative_position,
+        );
+        step.without(coverage)
+      });
+    let mut record = Record {
+      position: self.program.position,
+      relative_position: self.program.relative_position.clone(),
+      write: written.zip(old.clone()),
+      io: None,
+      coverage,
+    };
+    record.io = match self.program.step() {
+      Ok(Some(Event::AskForInputAgain)) => return Some(Stop::WaitingForInput),
+      Ok(Some(Event::Halted)) => return Some(Stop::Halted),
+      Err(e) => return Some(Stop::Error(e)),
+      Ok(Some(Event::Input(i))) | Ok(Some(Event::BreakOnInput(i))) => Some(Entry::Input(i)),
+      Ok(Some(Event::Output(o))) | Ok(Some(Event::BreakOnOutput(o))) => Some(Entry::Output(o)),
+      Ok(_) => None,
+    };
+    self.steps += 1;
+    if self.history.len() == self.history_limit {
+      self.history.pop_front();
+    }
+    self.history.push_back(record);
+    if let (Some(address), Some(old)) = (written, old) {
+      if self.watchpoints.contains(&address) {
+        let new = self.read(address);
+        return Some(Stop::Watchpoint { address, old, new });
+      }
+    }
+    if self.breakpoints.contains(&self.program.position) {
+      return Some(Stop::Breakpoint(self.program.position));
+    }
+    None
+  }
+  /// Steps until something stops the program.
+  pub fn run(&mut self) -> Stop<S::Word> {
+    loop {
+      if let Some(stop) = self.step() {
+        return stop;
+      }
+    }
+  }
+  /// Undoes the last step, or returns false if there is nothing left to undo. Limits, profile
+  /// and coverage are taken back along with memory. An output is only taken back while it is
+  /// still buffered in the system, so one the frontend already showed is not shown twice.
+  pub fn step_back(&mut self) -> bool {
+    let record = match self.history.pop_back() {
+      Some(record) => record,
+      None => return false,
+    };
+    if let Some((address, old)) = record.write {
+      self.program.poke(address, old);
+    }
+    match record.io {
+      Some(Entry::Input(i)) => self.program.system.unsend_input(i),
+      Some(Entry::Output(o)) => self.program.system.untake_output(&o),
+      None => {}
+    }
+    self.program.position = record.position;
+    self.program.relative_position = record.relative_position;
+    let opcode = self.program.opcode();
+    if let (Some(profile), Ok(opcode)) = (&mut self.program.profile, opcode) {
+      profile.unrecord(record.position, &opcode);
+    }
+    if let (Some(coverage), Some(added)) = (&mut self.program.coverage, &record.coverage) {
+      coverage.remove(added);
+    }
+    if let Some(limits) = &mut self.program.limits {
+      limits.stepped_back();
+    }
+    self.steps -= 1;
+    true
+  }
+  /// Steps back until `steps` instructions have executed, or as far as the history goes.
+  /// Returns whether it got there.
+  pub fn rewind(&mut self, steps: usize) -> bool {
+    while self.steps > steps {
+      if !self.step_back() {
+        return false;
+      }
+    }
+    self.steps == steps
+  }
+  /// Steps back to just before the last instruction that wrote to `address`, so that it is
+  /// the next to run. Does nothing and returns false if the history has no such write.
+  pub fn rewind_to_write(&mut self, address: usize) -> bool {
+    let writes = |r: &Record<S::Word>| matches!(r.write, Some((a, _)) if a == address);
+    match self.history.iter().rposition(writes) {
+      Some(n) => self.rewind(self.steps - (self.history.len() - n)),
+      None => false,
+    }
+  }
+  pub fn read(&self, address: usize) -> S::Word {
+    self.program.codes.get(address)
+  }
+}
+
+impl<S: System> Display for Debugger<S> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    writeln!(
+      f,
+      "position: {} relative_position: {} steps: {}",
+      self.program.position, self.program.relative_position, self.steps
+    )?;
+    for patch in &self.program.patches {
+      writeln!(f, "patched: {}", patch)?;
+    }
+    let position = self.program.position;
+    let words = self.program.codes.words(position, position + 4);
+    let mut line = Line::at(&words, 0);
+    line.address = position;
+    line.fmt(f)
+  }
+}
+
+impl<W: Word> Display for Stop<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::Breakpoint(address) => write!(f, "breakpoint at {}", address),
+      Self::Watchpoint { address, old, new } => {
+        write!(f, "watchpoint at {}: {} -> {}", address, old, new)
+      }
+      Self::WaitingForInput => write!(f, "waiting for input"),
+      Self::Halted => write!(f, "halted"),
+      Self::Error(e) => e.fmt(f),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::{assemble, Limits};
+
+  #[test]
+  fn test_breakpoints_and_watchpoints() {
+    let codes = assemble(
+      "
+      loop: add [n], #1, [n]
+            lt [n], #3, [flag]
+            jt [flag], #loop
+            in [n]
+            hlt
+      n:    .data 0
+      flag: .data 0
+      ",
+    )
+    .unwrap();
+    let program: Program = codes.into();
+    let mut debugger = Debugger::new(program);
+    debugger.breakpoints.insert(8);
+    debugger.watchpoints.insert(14);
+
+    let watch = |old, new| Stop::Watchpoint {
+      address: 14,
+      old,
+      new,
+    };
+    assert_eq!(debugger.run(), watch(0, 1));
+    assert_eq!(debugger.program.position, 4);
+    assert_eq!(debugger.run(), Stop::Breakpoint(8));
+    assert_eq!(debugger.run(), watch(1, 2));
+    debugger.watchpoints.clear();
+    assert_eq!(debugger.run(), Stop::Breakpoint(8));
+    assert_eq!(debugger.run(), Stop::Breakpoint(8));
+    assert_eq!(debugger.read(14), 3);
+    assert_eq!(debugger.run(), Stop::WaitingForInput);
+    assert_eq!(debugger.program.position, 11);
+    debugger.program.system.input.push_back(7);
+    assert_eq!(debugger.step(), None);
+    assert_eq!(debugger.run(), Stop::Halted);
+    assert_eq!(debugger.read(14), 7);
+    assert_eq!(debugger.steps, 10);
+    assert_eq!(debugger.run(), Stop::Halted);
+
+    // Back to before the last write to n, which was the input.
+    assert!(debugger.rewind_to_write(14));
+    assert_eq!(debugger.steps, 9);
+    assert_eq!((debugger.program.position, debugger.read(14)), (11, 3));
+    assert_eq!(debugger.program.system.input, [7]);
+    assert!(debugger.step_back());
+    assert_eq!(debugger.program.position, 8);
+    assert!(debugger.rewind(1));
+    assert_eq!((debugger.program.position, debugger.read(14)), (4, 1));
+    assert_eq!(debugger.run(), Stop::Breakpoint(8));
+    assert!(debugger.rewind(0));
+    assert_eq!(debugger.read(14), 0);
+    assert!(!debugger.step_back());
+
+    let program: Program = vec![1101, 1, 2, 3, 42].into();
+    let mut debugger = Debugger::new(program);
+    assert_eq!(debugger.step(), None);
+    assert_eq!(
+      debugger.step(),
+      Some(Stop::Error(IntcodeError::InvalidOpCode {
+        address: 4,
+        code: 42
+      }))
+    );
+  }
+
+  #[test]
+  fn test_step_back() {
+    let codes = assemble(
+      "
+      in [n]
+      out [n]
+      add [n], #1, [n]
+      out [n]
+      hlt
+      n: .data 0
+      ",
+    )
+    .unwrap();
+    let program = Program::from(codes)
+      .profiled()
+      .covered()
+      .limited(Limits::default().instructions(100))
+      .i(5);
+    let mut debugger = Debugger::new(program);
+    let executed = |debugger: &Debugger<_>| {
+      let program: &Program = &debugger.program;
+      let profile = program.profile.as_ref().unwrap();
+      let limits = program.limits.as_ref().unwrap();
+      (profile.instructions, profile.hits.len(), limits.executed())
+    };
+    assert_eq!(debugger.step(), None);
+    assert_eq!(debugger.step(), None);
+    let coverage = debugger.program.coverage.clone();
+    // The frontend shows and drains the first output.
+    assert_eq!(
+      debugger.program.system.output.drain(..).collect::<Vec<_>>(),
+      [5]
+    );
+    assert_eq!(debugger.step(), None);
+    assert_eq!(debugger.step(), None);
+    assert_eq!(debugger.program.system.output, [6]);
+    assert_eq!(executed(&debugger), (4, 4, 4));
+    assert_eq!(debugger.read(11), 6);
+
+    assert!(debugger.rewind(2));
+    assert_eq!(debugger.program.system.output, []);
+    assert_eq!(debugger.program.coverage, coverage);
+    assert_eq!(executed(&debugger), (2, 2, 2));
+    assert!(debugger.rewind(0));
+    assert_eq!(debugger.program.system.output, []);
+    assert_eq!(debugger.program.system.input, [5]);
+    assert_eq!(debugger.program.coverage, Some(Coverage::default()));
+    assert_eq!(executed(&debugger), (0, 0, 0));
+    assert_eq!(debugger.read(11), 0);
+  }
+}
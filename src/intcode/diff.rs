@@ -0,0 +1,161 @@
+use super::{disassemble, Line, Memory, Word, I, PAGE_SIZE};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Error, Formatter};
+
+/// A word that holds a different value in the second memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change<W = I> {
+  pub address: usize,
+  pub old: W,
+  pub new: W,
+}
+
+/// The differences between two memories, such as a program's image before and after a run or
+/// two snapshots.
+///
+/// Displayed as one line per changed word, followed by the instruction around it in both
+/// memories, `-` for the old one and `+` for the new.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff<W = I> {
+  pub changes: Vec<Change<W>>,
+  old: Memory<W>,
+  new: Memory<W>,
+}
+
+impl<W: Word> Diff<W> {
+  pub fn new(old: &Memory<W>, new: &Memory<W>) -> Self {
+    // Words outside every allocated page are zero in both.
+    let starts = old
+      .pages()
+      .into_iter()
+      .chain(new.pages())
+      .map(|(start, _)| start)
+      .collect::<BTreeSet<_>>();
+    let changes = starts
+      .into_iter()
+      .flat_map(|start| start..start + PAGE_SIZE)
+      .filter(|&address| old[address] != new[address])
+      .map(|address| Change {
+        address,
+        old: old.get(address),
+        new: new.get(address),
+      })
+      .collect();
+    Self {
+      changes,
+      old: old.clone(),
+      new: new.clone(),
+    }
+  }
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+}
+
+/// The words from address zero up to the first page that was never allocated. Listing any
+/// further would mean running through the sparse gaps.
+fn listed<W: Word>(memory: &Memory<W>) -> Vec<W> {
+  let mut end = 0;
+  for (start, words) in memory.pages() {
+    if start != end {
+      break;
+    }
+    end += words.len();
+  }
+  memory.words(0, end.min(memory.len()))
+}
+
+/// The line of a disassembly listing that `address` falls in.
+fn containing<'a, 'b, W>(lines: &'b [Line<'a, W>], address: usize) -> Option<&'b Line<'a, W>> {
+  let n = lines.partition_point(|line| line.address <= address);
+  let line = lines.get(n.checked_sub(1)?)?;
+  Some(line).filter(|line| address < line.address + line.words.len())
+}
+
+impl<W: Word> Display for Diff<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    writeln!(f, "changed: {} words", self.changes.len())?;
+    let (old, new) = (listed(&self.old), listed(&self.new));
+    let (old, new) = (disassemble(&old), disassemble(&new));
+    let context = |address| {
+      let line = |lines| containing(lines, address);
+      (line(&old), line(&new))
+    };
+    let address = |line: Option<&Line<'_, W>>| line.map(|line| line.address);
+    let mut changes = self.changes.iter().peekable();
+    while let Some(change) = changes.next() {
+      // Changes to the same instruction share its context.
+      let (before, after) = context(change.address);
+      let mut group = vec![change];
+      while let Some(next) = changes.peek() {
+        let (b, a) = context(next.address);
+        if (address(b), address(a)) != (address(before), address(after)) {
+          break;
+        }
+        group.push(changes.next().unwrap());
+      }
+      for change in group {
+        writeln!(f, "{:>6}: {} -> {}", change.address, change.old, change.new)?;
+      }
+      if let Some(line) = before {
+        writeln!(f, "- {}", line)?;
+      }
+      if let Some(line) = after {
+        writeln!(f, "+ {}", line)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::{Program, Snapshot};
+
+  #[test]
+  fn test_diff() {
+    let mut program = "1,0,0,0,99,0,0".parse::<Program>().unwrap();
+    program.codes.set(100, 0);
+    assert_eq!(program.to_string(), "1,0,0,0,99");
+    program.run().unwrap();
+    assert_eq!(program.to_string(), "2,0,0,0,99");
+    let mut far = "1101,5,0,1000000000000,99".parse::<Program>().unwrap();
+    far.run().unwrap();
+    assert_eq!(
+      far.to_string(),
+      "1101,5,0,1000000000000,99,0*999999999995,5"
+    );
+
+    let original = Memory::from(&program.original_codes[..]);
+    let diff = Diff::new(&original, &program.codes);
+    assert_eq!(
+      diff.to_string(),
+      "changed: 1 words\n     \
+            0: 1 -> 2\n\
+       -      0: 1,0,0,0                  add [0], [0], [0]\n\
+       +      0: 2,0,0,0                  mul [0], [0], [0]\n"
+    );
+
+    // Sparse writes far past the image, compared through snapshots.
+    let mut snapshot = program.snapshot();
+    let before = snapshot.memory();
+    snapshot.pages.push((1 << 40, vec![0, 7]));
+    let text = snapshot.to_string();
+    let after = text.parse::<Snapshot>().unwrap().memory();
+    let diff = Diff::new(&before, &after);
+    assert_eq!(
+      diff.changes,
+      [Change {
+        address: (1 << 40) + 1,
+        old: 0,
+        new: 7
+      }]
+    );
+    assert_eq!(
+      diff.to_string(),
+      "changed: 1 words\n1099511627777: 0 -> 7\n"
+    );
+    assert!(Diff::new(&before, &before).is_empty());
+  }
+}
@@ -0,0 +1,139 @@
+use super::{OpCode, ParameterMode, Word, I};
+use std::fmt::{Display, Error, Formatter};
+
+/// One line of a disassembly listing: a decoded instruction or a single word of data.
+#[derive(Copy, Clone, Debug)]
+pub struct Line<'a, W = I> {
+  pub address: usize,
+  pub words: &'a [W],
+  pub opcode: Option<OpCode<W>>,
+}
+
+impl<'a, W: Word> Line<'a, W> {
+  /// Decodes the line starting at `address`.
+  pub fn at(codes: &'a [W], address: usize) -> Self {
+    let opcode = OpCode::decode(codes, address)
+      .ok()
+      .filter(|opcode| address + opcode.size() <= codes.len());
+    let size = opcode.as_ref().map_or(1, OpCode::size);
+    Self {
+      address,
+      words: &codes[address..address + size],
+      opcode,
+    }
+  }
+}
+
+/// Linearly sweeps `codes`, decoding an instruction at each address and falling back to a
+/// single `.data` word wherever decoding fails.
+pub fn disassemble<W: Word>(codes: &[W]) -> Vec<Line<'_, W>> {
+  let mut lines = Vec::with_capacity(codes.len());
+  let mut address = 0;
+  while address < codes.len() {
+    let line = Line::at(codes, address);
+    address += line.words.len();
+    lines.push(line);
+  }
+  lines
+}
+
+impl<W: Word> OpCode<W> {
+  pub fn mnemonic(&self) -> &'static str {
+    match self {
+      Self::Add(_, _, _) => "add",
+      Self::Multiply(_, _, _) => "mul",
+      Self::Input(_) => "in",
+      Self::Output(_) => "out",
+      Self::Break => "hlt",
+      Self::JumpIfTrue(_, _) => "jt",
+      Self::JumpIfFalse(_, _) => "jf",
+      Self::LessThan(_, _, _) => "lt",
+      Self::Equals(_, _, _) => "eq",
+      Self::AdjustRelativeBase(_) => "arb",
+      Self::Custom { .. } => "op",
+    }
+  }
+  pub fn parameters(&self) -> Vec<ParameterMode<W>> {
+    match self {
+      Self::Add(p1, p2, p3)
+      | Self::Multiply(p1, p2, p3)
+      | Self::LessThan(p1, p2, p3)
+      | Self::Equals(p1, p2, p3) => vec![p1.clone(), p2.clone(), p3.clone()],
+      Self::JumpIfTrue(p1, p2) | Self::JumpIfFalse(p1, p2) => vec![p1.clone(), p2.clone()],
+      Self::Input(p1) | Self::Output(p1) | Self::AdjustRelativeBase(p1) => vec![p1.clone()],
+      Self::Break => vec![],
+      Self::Custom { inputs, output, .. } => inputs
+        .iter()
+        .chain(Some(output))
+        .flatten()
+        .cloned()
+        .collect(),
+    }
+  }
+}
+
+impl<W: Word> Display for ParameterMode<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::Position(i) => write!(f, "[{}]", i),
+      Self::Immediate(i) => write!(f, "#{}", i),
+      Self::Relative(i) if *i < W::default() => write!(f, "rb{}", i),
+      Self::Relative(i) => write!(f, "rb+{}", i),
+    }
+  }
+}
+
+impl<W: Word> Display for OpCode<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    f.write_str(self.mnemonic())?;
+    if let Self::Custom { code, .. } = self {
+      write!(f, "{}", code)?;
+    }
+    for (n, p) in self.parameters().iter().enumerate() {
+      write!(f, "{}{}", if n == 0 { " " } else { ", " }, p)?;
+    }
+    Ok(())
+  }
+}
+
+impl<W: Word> Display for Line<'_, W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    let words = self
+      .words
+      .iter()
+      .map(|w| w.to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+    write!(f, "{:>6}: {:<24} ", self.address, words)?;
+    match &self.opcode {
+      Some(opcode) => opcode.fmt(f),
+      None => write!(f, ".data {}", self.words[0]),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_disassemble() {
+    let codes = [1101, 5, 3, 20, 204, -1, 1006, 7, 0, 12345, 109, 3, 99, 1];
+    let listing = disassemble(&codes)
+      .iter()
+      .map(|l| l.to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      listing,
+      [
+        "     0: 1101,5,3,20              add #5, #3, [20]",
+        "     4: 204,-1                   out rb-1",
+        "     6: 1006,7,0                 jf [7], #0",
+        "     9: 12345                    .data 12345",
+        "    10: 109,3                    arb #3",
+        "    12: 99                       hlt",
+        "    13: 1                        .data 1",
+      ]
+    );
+  }
+}
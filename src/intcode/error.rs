@@ -0,0 +1,121 @@
+use super::{Word, I};
+use std::fmt::{Display, Error, Formatter};
+use std::time::Duration;
+
+/// Why a program could not execute the instruction at `address`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IntcodeError<W = I> {
+  InvalidOpCode {
+    address: usize,
+    code: W,
+  },
+  InvalidMode {
+    address: usize,
+    mode: I,
+  },
+  /// A parameter resolved to an address below zero.
+  NegativeAddress {
+    address: usize,
+    target: W,
+  },
+  /// A parameter resolved to an address too large to index memory with.
+  AddressOutOfRange {
+    address: usize,
+    target: W,
+  },
+  /// The system answered `Event::EndOfInput` to an input instruction.
+  InputExhausted {
+    address: usize,
+  },
+  WriteToImmediate {
+    address: usize,
+  },
+  /// An `Add`, `Multiply` or `AdjustRelativeBase` overflowed the word type while checked
+  /// arithmetic was on.
+  Overflow {
+    address: usize,
+  },
+  /// `Limits::instructions` ran out before the instruction at `address`.
+  BudgetExhausted {
+    address: usize,
+    instructions: u64,
+  },
+  /// `Limits::time` ran out before the instruction at `address`.
+  TimedOut {
+    address: usize,
+    time: Duration,
+  },
+  /// The program came back to a state it was in before without any IO in between.
+  InfiniteLoop {
+    address: usize,
+  },
+  /// The host side of the `Extension` registered for `code` reported a failure.
+  HostCallFailed {
+    address: usize,
+    code: I,
+  },
+}
+
+impl<W> IntcodeError<W> {
+  /// Address of the faulting instruction.
+  pub fn address(&self) -> usize {
+    match *self {
+      Self::InvalidOpCode { address, .. }
+      | Self::InvalidMode { address, .. }
+      | Self::NegativeAddress { address, .. }
+      | Self::AddressOutOfRange { address, .. }
+      | Self::InputExhausted { address }
+      | Self::WriteToImmediate { address }
+      | Self::Overflow { address }
+      | Self::BudgetExhausted { address, .. }
+      | Self::TimedOut { address, .. }
+      | Self::InfiniteLoop { address }
+      | Self::HostCallFailed { address, .. } => address,
+    }
+  }
+}
+
+impl<W: Word> Display for IntcodeError<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::InvalidOpCode { address, code } => {
+        write!(f, "invalid opcode {} at address {}", code, address)
+      }
+      Self::InvalidMode { address, mode } => {
+        write!(f, "invalid parameter mode {} at address {}", mode, address)
+      }
+      Self::NegativeAddress { address, target } => write!(
+        f,
+        "negative address {} used by the instruction at {}",
+        target, address
+      ),
+      Self::AddressOutOfRange { address, target } => write!(
+        f,
+        "address {} used by the instruction at {} is out of range",
+        target, address
+      ),
+      Self::InputExhausted { address } => write!(f, "input exhausted at address {}", address),
+      Self::WriteToImmediate { address } => {
+        write!(f, "immediate destination in the instruction at {}", address)
+      }
+      Self::Overflow { address } => write!(f, "arithmetic overflow at address {}", address),
+      Self::BudgetExhausted {
+        address,
+        instructions,
+      } => write!(
+        f,
+        "instruction budget of {} ran out at address {}",
+        instructions, address
+      ),
+      Self::TimedOut { address, time } => {
+        write!(f, "time limit of {:?} ran out at address {}", time, address)
+      }
+      Self::InfiniteLoop { address } => write!(f, "infinite loop at address {}", address),
+      Self::HostCallFailed { address, code } => {
+        write!(f, "host call {} failed at address {}", code, address)
+      }
+    }
+  }
+}
+
+impl<W: Word> std::error::Error for IntcodeError<W> {}
@@ -0,0 +1,200 @@
+use super::{IntcodeError, Word, I};
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::sync::Arc;
+
+type Procedure<W> = dyn Fn(&[W]) -> bool + Send + Sync;
+type Function<W> = dyn Fn(&[W]) -> Option<W> + Send + Sync;
+
+#[derive(Clone)]
+enum Call<W> {
+  Procedure(Arc<Procedure<W>>),
+  Function(Arc<Function<W>>),
+}
+
+/// An instruction outside the standard set, carried out by the host. Register one with
+/// `Program::extension` under an opcode the standard set does not use.
+#[derive(Clone)]
+pub struct Extension<W = I> {
+  pub name: String,
+  /// How many parameters the instruction reads, not counting the one a function writes to.
+  pub inputs: usize,
+  call: Call<W>,
+}
+
+impl<W: Word> Extension<W> {
+  /// An instruction that passes the values of up to three parameters to `f`, such as a debug
+  /// print or an assertion. The program fails if `f` returns false.
+  pub fn procedure(
+    name: impl Into<String>,
+    inputs: usize,
+    f: impl Fn(&[W]) -> bool + Send + Sync + 'static,
+  ) -> Result<Self, TooManyInputs> {
+    if inputs > 3 {
+      return Err(TooManyInputs { inputs, max: 3 });
+    }
+    Ok(Self {
+      name: name.into(),
+      inputs,
+      call: Call::Procedure(Arc::new(f)),
+    })
+  }
+  /// An instruction that passes the values of up to two parameters to `f` and writes what it
+  /// returns to one more, such as a host call for a random number. The program fails if `f`
+  /// returns `None`.
+  pub fn function(
+    name: impl Into<String>,
+    inputs: usize,
+    f: impl Fn(&[W]) -> Option<W> + Send + Sync + 'static,
+  ) -> Result<Self, TooManyInputs> {
+    if inputs > 2 {
+      return Err(TooManyInputs { inputs, max: 2 });
+    }
+    Ok(Self {
+      name: name.into(),
+      inputs,
+      call: Call::Function(Arc::new(f)),
+    })
+  }
+  /// Whether the instruction writes its result to a last parameter.
+  pub fn has_output(&self) -> bool {
+    matches!(self.call, Call::Function(_))
+  }
+  /// Runs the host side of the instruction registered for `code` at `address`, returning the
+  /// value to write, if any.
+  pub(super) fn call(
+    &self,
+    address: usize,
+    code: I,
+    values: &[W],
+  ) -> Result<Option<W>, IntcodeError<W>> {
+    let failed = IntcodeError::HostCallFailed { address, code };
+    match &self.call {
+      Call::Procedure(f) if f(values) => Ok(None),
+      Call::Procedure(_) => Err(failed),
+      Call::Function(f) => f(values).map(Some).ok_or(failed),
+    }
+  }
+}
+
+/// An opcode that cannot be given to an extension, because the standard set uses it or it
+/// does not fit in the two digits an instruction has for it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReservedOpCode {
+  pub code: I,
+}
+
+impl Display for ReservedOpCode {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    write!(f, "opcode {} is standard or out of range", self.code)
+  }
+}
+
+impl std::error::Error for ReservedOpCode {}
+
+/// More parameters than an extension can read: an instruction has at most three, and a
+/// function writes its result to the last of them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TooManyInputs {
+  pub inputs: usize,
+  pub max: usize,
+}
+
+impl Display for TooManyInputs {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    write!(
+      f,
+      "{} inputs given where at most {} fit",
+      self.inputs, self.max
+    )
+  }
+}
+
+impl std::error::Error for TooManyInputs {}
+
+impl<W> Debug for Extension<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    f.debug_struct("Extension")
+      .field("name", &self.name)
+      .field("inputs", &self.inputs)
+      .field("output", &matches!(self.call, Call::Function(_)))
+      .finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::intcode::{
+    assemble, Extension, IntcodeError, Program, Queue, ReservedOpCode, TooManyInputs, I,
+  };
+  use std::sync::atomic::{AtomicI64, Ordering};
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn test_extensions() {
+    let codes = assemble(
+      "
+      op42 #7, [x], [x]
+      op43 [x], #1
+      op44 [x]
+      out [x]
+      op43 [x], #4
+      hlt
+      x: .data 0
+      ",
+    )
+    .unwrap();
+    let printed = Arc::new(Mutex::new(vec![]));
+    let log = printed.clone();
+    let seed = AtomicI64::new(4);
+    let program = Program::<Queue>::from(codes.clone())
+      .extension(
+        42,
+        Extension::function("random", 2, move |_: &[I]| {
+          Some(seed.fetch_add(1, Ordering::Relaxed))
+        })
+        .unwrap(),
+      )
+      .and_then(|p| {
+        let assert_ne = Extension::procedure("assert ne", 2, |v| v[0] != v[1]);
+        p.extension(43, assert_ne.unwrap())
+      })
+      .and_then(|p| {
+        let print = Extension::procedure("print", 1, move |v| {
+          log.lock().unwrap().push(v[0]);
+          true
+        });
+        p.extension(44, print.unwrap())
+      })
+      .unwrap();
+    let mut failing = program.clone();
+    assert_eq!(
+      failing.run(),
+      Err(IntcodeError::HostCallFailed {
+        address: 11,
+        code: 43
+      })
+    );
+    assert_eq!(failing.system.output, [4]);
+    assert_eq!(*printed.lock().unwrap(), [4]);
+
+    // Without the extensions registered the same program does not run at all.
+    let error = Program::<Queue>::from(codes.clone()).run().unwrap_err();
+    assert_eq!(
+      error,
+      IntcodeError::InvalidOpCode {
+        address: 0,
+        code: 142
+      }
+    );
+
+    for code in [1, 9, 99, 100, -42] {
+      let nop = Extension::procedure("nop", 0, |_: &[I]| true).unwrap();
+      let result = Program::<Queue>::from(codes.clone()).extension(code, nop);
+      assert_eq!(result.unwrap_err(), ReservedOpCode { code });
+    }
+    let error = Extension::procedure("nop", 4, |_: &[I]| true).unwrap_err();
+    assert_eq!(error, TooManyInputs { inputs: 4, max: 3 });
+    let error = Extension::function("zero", 3, |_: &[I]| Some(0)).unwrap_err();
+    assert_eq!(error, TooManyInputs { inputs: 3, max: 2 });
+  }
+}
@@ -0,0 +1,170 @@
+use super::{IntcodeError, Memory, OpCode, Word, I};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/// How often the clock is read, in instructions.
+const CLOCK_INTERVAL: u64 = 1024;
+/// How many state hashes loop detection keeps before starting over. Any loop shorter than this
+/// still comes round twice within the next batch.
+const MAX_STATES: usize = 1 << 20;
+
+/// Bounds on how long a program may run, checked before each instruction.
+///
+/// Loop detection hashes the position, relative base and memory before every instruction and
+/// fails when a state comes round again with no input or output in between, which is a loop
+/// the program can never leave. A repeated hash only marks the state as a suspect; the loop is
+/// reported once the same full state turns up again, so a hash collision never fails a run.
+#[derive(Clone, Debug, Default)]
+pub struct Limits<W = I> {
+  pub instructions: Option<u64>,
+  pub time: Option<Duration>,
+  pub detect_loops: bool,
+  executed: u64,
+  started: Option<Instant>,
+  memory_hash: u64,
+  seen: HashSet<u64>,
+  suspect: Option<State<W>>,
+}
+
+/// Everything an instruction's behaviour depends on, apart from input.
+#[derive(Clone, Debug)]
+struct State<W> {
+  hash: u64,
+  address: usize,
+  relative_position: W,
+  memory: Memory<W>,
+}
+
+/// States are the same when memory holds the same words, even if it grew differently.
+impl<W: Word> PartialEq for State<W> {
+  fn eq(&self, other: &Self) -> bool {
+    self.hash == other.hash
+      && self.address == other.address
+      && self.relative_position == other.relative_position
+      && self.memory.same_words(&other.memory)
+  }
+}
+
+impl<W: Word> Limits<W> {
+  pub fn instructions(mut self, instructions: u64) -> Self {
+    self.instructions = Some(instructions);
+    self
+  }
+  pub fn time(mut self, time: Duration) -> Self {
+    self.time = Some(time);
+    self
+  }
+  pub fn detect_loops(mut self) -> Self {
+    self.detect_loops = true;
+    self
+  }
+  /// Instructions executed since the limits were set or the program was reset.
+  pub fn executed(&self) -> u64 {
+    self.executed
+  }
+  /// Starts counting from zero again, for a program that was reset or restored.
+  pub fn restart(&mut self) {
+    self.executed = 0;
+    self.started = None;
+    self.memory_hash = 0;
+    self.seen.clear();
+    self.suspect = None;
+  }
+  /// Fails if running `opcode` at `address` would break one of the limits.
+  // `u64::is_multiple_of` only arrived in Rust 1.87.
+  #[allow(clippy::manual_is_multiple_of)]
+  pub fn check(
+    &mut self,
+    address: usize,
+    opcode: &OpCode<W>,
+    relative_position: &W,
+    memory: &Memory<W>,
+  ) -> Result<(), IntcodeError<W>> {
+    if let Some(instructions) = self.instructions {
+      if self.executed >= instructions {
+        return Err(IntcodeError::BudgetExhausted {
+          address,
+          instructions,
+        });
+      }
+    }
+    if let Some(time) = self.time {
+      let started = *self.started.get_or_insert_with(Instant::now);
+      if self.executed % CLOCK_INTERVAL == 0 && started.elapsed() >= time {
+        return Err(IntcodeError::TimedOut { address, time });
+      }
+    }
+    if self.detect_loops {
+      match opcode {
+        OpCode::Input(_) | OpCode::Output(_) => {
+          self.seen.clear();
+          self.suspect = None;
+        }
+        _ => {
+          let mut hasher = DefaultHasher::new();
+          (address, relative_position, self.memory_hash).hash(&mut hasher);
+          let hash = hasher.finish();
+          if self.seen.len() >= MAX_STATES {
+            self.seen.clear();
+          }
+          if !self.seen.insert(hash) {
+            self.repeated(hash, address, relative_position, memory)?;
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+  /// Follows up a state whose hash was seen before. The first such state is kept, and the loop
+  /// is certain once it comes round again; a different state with the same hash was a
+  /// collision, so it is dropped and the next repeat becomes the suspect.
+  fn repeated(
+    &mut self,
+    hash: u64,
+    address: usize,
+    relative_position: &W,
+    memory: &Memory<W>,
+  ) -> Result<(), IntcodeError<W>> {
+    let state = State {
+      hash,
+      address,
+      relative_position: relative_position.clone(),
+      memory: memory.clone(),
+    };
+    match self.suspect.take() {
+      None => self.suspect = Some(state),
+      Some(suspect) if suspect == state => return Err(IntcodeError::InfiniteLoop { address }),
+      Some(suspect) if suspect.hash != hash => self.suspect = Some(suspect),
+      Some(_) => {}
+    }
+    Ok(())
+  }
+  pub fn executed_one(&mut self) {
+    self.executed += 1;
+  }
+  /// Takes back an executed instruction, for a step that was undone. The states seen so far
+  /// are forgotten, so a loop is only reported once it comes round again from here.
+  pub fn stepped_back(&mut self) {
+    self.executed = self.executed.saturating_sub(1);
+    self.seen.clear();
+    self.suspect = None;
+  }
+  /// Keeps the memory hash up to date. It is a sum over written cells, so it only depends on
+  /// what memory holds, not on the order of the writes.
+  pub fn wrote(&mut self, address: usize, old: &W, new: &W) {
+    if self.detect_loops {
+      self.memory_hash = self
+        .memory_hash
+        .wrapping_sub(cell_hash(address, old))
+        .wrapping_add(cell_hash(address, new));
+    }
+  }
+}
+
+fn cell_hash<W: Word>(address: usize, value: &W) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  (address, value).hash(&mut hasher);
+  hasher.finish()
+}
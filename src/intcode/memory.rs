@@ -0,0 +1,214 @@
+use super::{Word, I};
+use std::collections::HashMap;
+use std::fmt::{Display, Error, Formatter};
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+pub const PAGE_SIZE: usize = 1024;
+/// Pages below this index live in a directly indexed table, the rest in a hash map.
+const NEAR_PAGES: usize = 1 << 14;
+
+/// Pages are shared between clones and copied on their first write, so cloning memory is cheap
+/// and clones only pay for the pages they change.
+type Page<W> = Arc<[W]>;
+
+/// Sparse program memory. Every address reads as zero until written, and pages are only
+/// allocated for the regions a program actually touches.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Memory<W = I> {
+  near: Vec<Option<Page<W>>>,
+  far: HashMap<usize, Page<W>>,
+  len: usize,
+  zero: W,
+}
+
+impl<W: Word> Memory<W> {
+  pub fn get(&self, address: usize) -> W {
+    self[address].clone()
+  }
+  pub fn set(&mut self, address: usize, value: W) {
+    if value.is_zero() && self.page(address / PAGE_SIZE).is_none() {
+      self.len = self.len.max(address + 1);
+      return;
+    }
+    self[address] = value;
+  }
+  /// One past the highest address loaded or written.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+  /// The words in `start..end`, reading unset ones as zero.
+  pub fn words(&self, start: usize, end: usize) -> Vec<W> {
+    (start..end).map(|address| self.get(address)).collect()
+  }
+  /// Whether both memories hold the same word at every address, however their pages were
+  /// allocated and whatever their `len`.
+  pub fn same_words(&self, other: &Self) -> bool {
+    let covers = |a: &Self, b: &Self| {
+      a.pages()
+        .into_iter()
+        .all(|(start, words)| match b.page(start / PAGE_SIZE) {
+          Some(page) => **page == *words,
+          None => words.iter().all(W::is_zero),
+        })
+    };
+    covers(self, other) && covers(other, self)
+  }
+  /// Start addresses and contents of every allocated page, in address order.
+  pub fn pages(&self) -> Vec<(usize, &[W])> {
+    let mut pages = self
+      .near
+      .iter()
+      .enumerate()
+      .filter_map(|(n, page)| page.as_ref().map(|page| (n, page)))
+      .chain(self.far.iter().map(|(n, page)| (*n, page)))
+      .map(|(n, page)| (n * PAGE_SIZE, &page[..]))
+      .collect::<Vec<_>>();
+    pages.sort_unstable_by_key(|(start, _)| *start);
+    pages
+  }
+  fn page(&self, n: usize) -> Option<&Page<W>> {
+    if n < NEAR_PAGES {
+      self.near.get(n)?.as_ref()
+    } else {
+      self.far.get(&n)
+    }
+  }
+  fn page_mut(&mut self, n: usize) -> &mut [W] {
+    let new_page = || vec![W::default(); PAGE_SIZE].into();
+    let page = if n < NEAR_PAGES {
+      if self.near.len() <= n {
+        self.near.resize_with(n + 1, || None);
+      }
+      self.near[n].get_or_insert_with(new_page)
+    } else {
+      self.far.entry(n).or_insert_with(new_page)
+    };
+    Arc::make_mut(page)
+  }
+}
+
+impl<W: Word> Index<usize> for Memory<W> {
+  type Output = W;
+
+  fn index(&self, address: usize) -> &W {
+    self
+      .page(address / PAGE_SIZE)
+      .map_or(&self.zero, |page| &page[address % PAGE_SIZE])
+  }
+}
+
+impl<W: Word> IndexMut<usize> for Memory<W> {
+  fn index_mut(&mut self, address: usize) -> &mut W {
+    self.len = self.len.max(address + 1);
+    &mut self.page_mut(address / PAGE_SIZE)[address % PAGE_SIZE]
+  }
+}
+
+impl<W: Word> Display for Memory<W> {
+  /// The words from address zero up to the last one that is not zero, comma separated the way
+  /// an input file writes them. Every word past it reads as zero anyway, so memories holding
+  /// the same values print the same however far they were grown.
+  ///
+  /// Runs of at least `PAGE_SIZE` zeros are written `0*<count>`, so a write far out in memory
+  /// does not turn into terabytes of text. Only text without such runs loads as a program.
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    let mut first = true;
+    let mut word = |f: &mut Formatter<'_>, text: &dyn Display| {
+      let comma = if first { "" } else { "," };
+      first = false;
+      write!(f, "{}{}", comma, text)
+    };
+    // Zeros are only written once a word that is not zero follows them.
+    let (mut next, mut zeros) = (0, 0);
+    for (start, words) in self.pages() {
+      zeros += start - next;
+      for value in words {
+        if value.is_zero() {
+          zeros += 1;
+          continue;
+        }
+        if zeros >= PAGE_SIZE {
+          word(f, &format_args!("0*{}", zeros))?;
+        } else {
+          for _ in 0..zeros {
+            word(f, &0)?;
+          }
+        }
+        zeros = 0;
+        word(f, value)?;
+      }
+      next = start + words.len();
+    }
+    Ok(())
+  }
+}
+
+impl<W: Word> From<&[W]> for Memory<W> {
+  fn from(codes: &[W]) -> Self {
+    let mut memory = Self::default();
+    for (n, chunk) in codes.chunks(PAGE_SIZE).enumerate() {
+      memory.page_mut(n)[..chunk.len()].clone_from_slice(chunk);
+    }
+    memory.len = codes.len();
+    memory
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sparse() {
+    let mut memory: Memory = Memory::from(&[1, 2, 3][..]);
+    assert_eq!((memory[2], memory[3], memory.len()), (3, 0, 3));
+    memory.set(1_000_000_000_000, 42);
+    memory[5000] = 7;
+    memory.set(2_000_000_000_000, 0);
+    assert_eq!(memory.get(1_000_000_000_000), 42);
+    assert_eq!(memory.get(1_000_000_000_001), 0);
+    assert_eq!(memory.words(4999, 5002), [0, 7, 0]);
+    assert_eq!(memory.len(), 2_000_000_000_001);
+    let starts = memory
+      .pages()
+      .iter()
+      .map(|(start, _)| *start)
+      .collect::<Vec<_>>();
+    assert_eq!(starts, [0, 4096, 1_000_000_000_000]);
+
+    let mut copy = memory.clone();
+    copy[1] = 5;
+    assert_eq!((memory[1], copy[1], copy[2]), (2, 5, 3));
+
+    let small = Memory::from(&[1, 2, 3][..]);
+    let mut grown = small.clone();
+    grown[7000] = 1;
+    grown[7000] = 0;
+    grown.set(9000, 0);
+    assert_ne!(grown, small);
+    assert!(grown.same_words(&small) && small.same_words(&grown));
+    grown[0] = 4;
+    assert!(!grown.same_words(&small));
+    assert!(!small.same_words(&memory));
+  }
+
+  #[test]
+  fn test_display() {
+    let mut memory: Memory = Memory::from(&[1, 0, 0, 2, 0][..]);
+    assert_eq!(memory.to_string(), "1,0,0,2");
+    memory.set(1_000_000_000_000, 5);
+    memory.set(2_000_000_000_000, 0);
+    assert_eq!(memory.to_string(), "1,0,0,2,0*999999999996,5");
+    // Allocated zeros print the same as unallocated ones.
+    let mut grown = Memory::from(&[1, 0, 0, 2][..]);
+    grown[7000] = 1;
+    grown[7000] = 0;
+    grown.set(1_000_000_000_000, 5);
+    assert_eq!(grown.to_string(), memory.to_string());
+    assert_eq!(Memory::<I>::default().to_string(), "");
+  }
+}
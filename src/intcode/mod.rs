@@ -0,0 +1,945 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+mod assemble;
+mod batch;
+mod cache;
+mod cfg;
+mod coverage;
+mod debugger;
+mod diff;
+mod disassemble;
+mod error;
+mod extension;
+mod limits;
+mod memory;
+mod network;
+mod outputs;
+mod parse;
+mod patch;
+mod profile;
+mod scanner;
+mod snapshot;
+mod symbolic;
+mod transcript;
+mod translate;
+mod word;
+
+pub use assemble::{assemble, AssembleError};
+pub use batch::Batch;
+pub use cache::DecodeCache;
+pub use cfg::{Block, Cfg, Exit};
+pub use coverage::Coverage;
+pub use debugger::{Debugger, Record, Stop};
+pub use diff::{Change, Diff};
+pub use disassemble::{disassemble, Line};
+pub use error::IntcodeError;
+pub use extension::{Extension, ReservedOpCode, TooManyInputs};
+pub use limits::Limits;
+pub use memory::{Memory, PAGE_SIZE};
+pub use network::{Channels, Network};
+pub use outputs::Outputs;
+pub use parse::{parse_annotated, parse_codes, ParseError};
+pub use patch::{parse_patches, Patch};
+pub use profile::Profile;
+pub use scanner::{Scan, Scanner};
+pub use snapshot::Snapshot;
+pub use symbolic::{Atom, Condition, End, Expr, Path, Symbolic};
+pub use transcript::{Entry, Recorder, ReplayError, Transcript};
+pub use translate::translate;
+pub use word::Word;
+
+/// The default word type.
+pub type I = i64;
+
+/// What a `System` wants the program to do after an input or output instruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event<W = I> {
+  /// Store the value and keep running.
+  Input(W),
+  /// Store the value and return from `run_to_event`.
+  BreakOnInput(W),
+  /// No input is available yet. The program stops on the input instruction and asks again
+  /// the next time it is run.
+  AskForInputAgain,
+  /// Keep running after an output.
+  Output(W),
+  /// Return from `run_to_event` after an output.
+  BreakOnOutput(W),
+  Halted,
+  /// No input will ever arrive. The program fails with `IntcodeError::InputExhausted`.
+  EndOfInput,
+}
+
+pub trait System {
+  /// The word type of programs attached to this system.
+  type Word: Word;
+
+  fn send_input(&mut self) -> Event<Self::Word>;
+  fn take_output(&mut self, output: Self::Word) -> Event<Self::Word>;
+  /// Called by `run` once the program halts or stops waiting for input.
+  fn end(&mut self) -> Option<Self::Word> {
+    None
+  }
+  fn reset(&mut self) {}
+  /// State to store in a `Snapshot`, such as buffered IO.
+  fn save_state(&self) -> Vec<Self::Word> {
+    vec![]
+  }
+  fn restore_state(&mut self, _state: &[Self::Word]) {}
+  /// Takes back an input that a debugger stepped back over, so it is sent again.
+  fn unsend_input(&mut self, _input: Self::Word) {}
+  /// Takes back an output that a debugger stepped back over, if it is still buffered. Outputs
+  /// already passed on stay passed on.
+  fn untake_output(&mut self, _output: &Self::Word) {}
+}
+
+/// A `System` that feeds inputs from a queue and collects every output, breaking after each one.
+#[derive(Clone, Debug, Default)]
+pub struct Queue<W = I> {
+  pub input: VecDeque<W>,
+  pub output: Vec<W>,
+}
+
+impl<W: Word> System for Queue<W> {
+  type Word = W;
+
+  fn send_input(&mut self) -> Event<W> {
+    match self.input.pop_front() {
+      Some(i) => Event::Input(i),
+      None => Event::AskForInputAgain,
+    }
+  }
+
+  fn take_output(&mut self, output: W) -> Event<W> {
+    self.output.push(output.clone());
+    Event::BreakOnOutput(output)
+  }
+
+  fn reset(&mut self) {
+    self.input.clear();
+    self.output.clear();
+  }
+
+  fn save_state(&self) -> Vec<W> {
+    let mut state = vec![W::from_i64(self.input.len() as i64)];
+    state.extend(self.input.iter().cloned());
+    state.extend(self.output.iter().cloned());
+    state
+  }
+
+  fn restore_state(&mut self, state: &[W]) {
+    let inputs = state
+      .first()
+      .and_then(W::to_i64)
+      .map_or(0, |n| n as usize)
+      .min(state.len().saturating_sub(1));
+    self.input = state[1..=inputs].iter().cloned().collect();
+    self.output = state[1 + inputs..].to_vec();
+  }
+
+  fn unsend_input(&mut self, input: W) {
+    self.input.push_front(input);
+  }
+
+  fn untake_output(&mut self, output: &W) {
+    if self.output.last() == Some(output) {
+      self.output.pop();
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Program<S: System = Queue> {
+  pub name: String,
+  pub codes: Memory<S::Word>,
+  pub original_codes: Vec<S::Word>,
+  pub position: usize,
+  pub relative_position: S::Word,
+  pub reset_after_run: bool,
+  /// Report overflowing `Add`, `Multiply` and `AdjustRelativeBase` as errors instead of wrapping.
+  pub checked: bool,
+  pub cache: Option<DecodeCache<S::Word>>,
+  pub profile: Option<Profile>,
+  pub coverage: Option<Coverage>,
+  pub limits: Option<Limits<S::Word>>,
+  /// Patches applied to the image since it was loaded, in order. Those past the end of
+  /// `original_codes` are only kept here, and applied again on every reset.
+  pub patches: Vec<Patch<S::Word>>,
+  /// Instructions beyond the standard set, by opcode.
+  pub extensions: BTreeMap<I, Extension<S::Word>>,
+  pub system: S,
+}
+
+impl<S: System> Program<S> {
+  pub fn name(mut self, s: impl Into<String>) -> Self {
+    self.name = s.into();
+    self
+  }
+  /// Turns on checked arithmetic.
+  pub fn checked(mut self) -> Self {
+    self.checked = true;
+    self
+  }
+  /// Turns on the decode cache.
+  pub fn cached(mut self) -> Self {
+    self.cache = Some(DecodeCache::default());
+    self
+  }
+  /// Turns on profiling.
+  pub fn profiled(mut self) -> Self {
+    self.profile = Some(Profile::default());
+    self
+  }
+  /// Turns on coverage tracking.
+  pub fn covered(mut self) -> Self {
+    self.coverage = Some(Coverage::default());
+    self
+  }
+  pub fn limited(mut self, limits: Limits<S::Word>) -> Self {
+    self.limits = Some(limits);
+    self
+  }
+  /// Registers an instruction for `code`, which must be a two digit opcode the standard set
+  /// does not use. Programs that never use it run exactly as before.
+  pub fn extension(
+    mut self,
+    code: I,
+    extension: Extension<S::Word>,
+  ) -> Result<Self, ReservedOpCode> {
+    if !(10..99).contains(&code) {
+      return Err(ReservedOpCode { code });
+    }
+    self.extensions.insert(code, extension);
+    if let Some(cache) = &mut self.cache {
+      cache.clear();
+    }
+    Ok(self)
+  }
+  /// The same program in the same state, attached to another system with the same word type.
+  pub fn with_system<T: System<Word = S::Word>>(self, system: T) -> Program<T> {
+    self.map_system(|_| system)
+  }
+  /// The same program in the same state, attached to a system built from its current one.
+  pub fn map_system<T: System<Word = S::Word>>(self, f: impl FnOnce(S) -> T) -> Program<T> {
+    Program {
+      name: self.name,
+      codes: self.codes,
+      original_codes: self.original_codes,
+      position: self.position,
+      relative_position: self.relative_position,
+      reset_after_run: self.reset_after_run,
+      checked: self.checked,
+      cache: self.cache,
+      profile: self.profile,
+      coverage: self.coverage,
+      limits: self.limits,
+      patches: self.patches,
+      extensions: self.extensions,
+      system: f(self.system),
+    }
+  }
+  pub fn reset(&mut self) {
+    let image = self.image();
+    self.restart(image);
+  }
+  /// Resets everything but the memory, which is replaced with `codes`.
+  fn restart(&mut self, codes: Memory<S::Word>) {
+    self.position = 0;
+    self.relative_position = S::Word::default();
+    if let Some(cache) = &mut self.cache {
+      // The images only differ where the program wrote, and only instructions overlapping a
+      // word they differ in need decoding again.
+      let old = &self.codes;
+      cache.retain_written(|address, opcode| {
+        (address..address + opcode.size()).all(|a| old[a] == codes[a])
+      });
+    }
+    self.codes = codes;
+    if let Some(limits) = &mut self.limits {
+      limits.restart();
+    }
+    self.system.reset();
+  }
+  /// Runs until the program halts or the system stops supplying input, then returns `System::end`.
+  pub fn run(&mut self) -> Result<Option<S::Word>, IntcodeError<S::Word>> {
+    loop {
+      match self.run_to_event()? {
+        Event::Halted | Event::AskForInputAgain => break,
+        _ => {}
+      }
+    }
+    let output = self.system.end();
+    if self.reset_after_run {
+      self.reset();
+    }
+    Ok(output)
+  }
+  /// Runs until the system asks for a break, input runs dry or the program halts.
+  pub fn run_to_event(&mut self) -> Result<Event<S::Word>, IntcodeError<S::Word>> {
+    loop {
+      match self.step()? {
+        Some(Event::Input(_)) | Some(Event::Output(_)) | None => {}
+        Some(e) => return Ok(e),
+      }
+    }
+  }
+  /// Executes a single instruction. The position is left alone when the program halts, has to
+  /// wait for input or fails, so stepping again retries the same instruction.
+  pub fn step(&mut self) -> Result<Option<Event<S::Word>>, IntcodeError<S::Word>> {
+    let position = self.position;
+    let opcode = self.fetch()?;
+    if let Some(limits) = &mut self.limits {
+      limits.check(position, &opcode, &self.relative_position, &self.codes)?;
+    }
+    let relative_position = self
+      .coverage
+      .as_ref()
+      .map(|_| self.relative_position.clone());
+    let event = self.apply(&opcode)?;
+    match event {
+      Some(Event::AskForInputAgain) => return Ok(event),
+      Some(Event::Halted) => {}
+      _ => self.move_position(&opcode)?,
+    }
+    if let Some(limits) = &mut self.limits {
+      limits.executed_one();
+    }
+    if let Some(profile) = &mut self.profile {
+      profile.record(position, &opcode);
+    }
+    if let (Some(coverage), Some(relative_position)) = (&mut self.coverage, relative_position) {
+      coverage.record(position, &opcode, &relative_position);
+    }
+    Ok(event)
+  }
+  pub fn opcode(&self) -> Result<OpCode<S::Word>, IntcodeError<S::Word>> {
+    OpCode::decode_extended(
+      |address| self.codes[address].clone(),
+      self.position,
+      &self.extensions,
+    )
+  }
+  fn fetch(&mut self) -> Result<OpCode<S::Word>, IntcodeError<S::Word>> {
+    let position = self.position;
+    if let Some(opcode) = self.cache.as_mut().and_then(|cache| cache.get(position)) {
+      return Ok(opcode);
+    }
+    let opcode = self.opcode()?;
+    if let Some(cache) = &mut self.cache {
+      cache.insert(position, opcode.clone());
+    }
+    Ok(opcode)
+  }
+  pub fn move_position(&mut self, opcode: &OpCode<S::Word>) -> Result<(), IntcodeError<S::Word>> {
+    match opcode {
+      OpCode::Break => {}
+      OpCode::JumpIfTrue(p1, p2) => {
+        if !self.value(p1)?.is_zero() {
+          self.position = self.jump_target(p2)?;
+        } else {
+          self.position += 3;
+        }
+      }
+      OpCode::JumpIfFalse(p1, p2) => {
+        if self.value(p1)?.is_zero() {
+          self.position = self.jump_target(p2)?;
+        } else {
+          self.position += 3
+        }
+      }
+      _ => self.position += opcode.size(),
+    };
+    Ok(())
+  }
+  /// Executes everything but the jump, returning the event for IO and halt instructions.
+  pub fn apply(
+    &mut self,
+    opcode: &OpCode<S::Word>,
+  ) -> Result<Option<Event<S::Word>>, IntcodeError<S::Word>> {
+    let overflow = IntcodeError::Overflow {
+      address: self.position,
+    };
+    match opcode {
+      OpCode::Add(p1, p2, p3) => {
+        let (a, b) = (self.value(p1)?, self.value(p2)?);
+        let value = if self.checked {
+          a.checked_add(&b).ok_or(overflow)?
+        } else {
+          a.wrapping_add(&b)
+        };
+        self.write(p3, value)?;
+      }
+      OpCode::Multiply(p1, p2, p3) => {
+        let (a, b) = (self.value(p1)?, self.value(p2)?);
+        let value = if self.checked {
+          a.checked_mul(&b).ok_or(overflow)?
+        } else {
+          a.wrapping_mul(&b)
+        };
+        self.write(p3, value)?;
+      }
+      OpCode::Input(p1) => {
+        let destination = self.destination(p1)?;
+        let input = self.system.send_input();
+        match &input {
+          Event::Input(i) | Event::BreakOnInput(i) => self.write_at(destination, i.clone()),
+          Event::EndOfInput => {
+            return Err(IntcodeError::InputExhausted {
+              address: self.position,
+            })
+          }
+          _ => {}
+        }
+        return Ok(Some(input));
+      }
+      OpCode::Output(p1) => {
+        let value = self.value(p1)?;
+        return Ok(Some(self.system.take_output(value)));
+      }
+      OpCode::Break => return Ok(Some(Event::Halted)),
+      OpCode::JumpIfTrue(_, _) | OpCode::JumpIfFalse(_, _) => {}
+      OpCode::LessThan(p1, p2, p3) => {
+        let value = self.value(p1)? < self.value(p2)?;
+        self.write(p3, S::Word::from_i64(value as i64))?;
+      }
+      OpCode::Equals(p1, p2, p3) => {
+        let value = self.value(p1)? == self.value(p2)?;
+        self.write(p3, S::Word::from_i64(value as i64))?;
+      }
+      OpCode::AdjustRelativeBase(p1) => {
+        let (a, b) = (&self.relative_position, self.value(p1)?);
+        self.relative_position = if self.checked {
+          a.checked_add(&b).ok_or(overflow)?
+        } else {
+          a.wrapping_add(&b)
+        };
+      }
+      OpCode::Custom {
+        code,
+        inputs,
+        output,
+      } => {
+        let extension = self
+          .extensions
+          .get(code)
+          .ok_or(IntcodeError::HostCallFailed {
+            address: self.position,
+            code: *code,
+          })?
+          .clone();
+        let values = inputs
+          .iter()
+          .flatten()
+          .map(|p| self.value(p))
+          .collect::<Result<Vec<_>, _>>()?;
+        if let (Some(value), Some(p)) = (extension.call(self.position, *code, &values)?, output) {
+          self.write(p, value)?;
+        }
+      }
+    }
+    Ok(None)
+  }
+  /// Reads a parameter of the instruction at `position`.
+  pub fn value(&self, p: &ParameterMode<S::Word>) -> Result<S::Word, IntcodeError<S::Word>> {
+    match p.address(&self.relative_position) {
+      Some(address) => Ok(self.codes[self.to_address(address)?].clone()),
+      None => Ok(p.word()),
+    }
+  }
+  /// The address a parameter of the instruction at `position` writes to.
+  pub fn destination(&self, p: &ParameterMode<S::Word>) -> Result<usize, IntcodeError<S::Word>> {
+    match p.address(&self.relative_position) {
+      Some(address) => self.to_address(address),
+      None => Err(IntcodeError::WriteToImmediate {
+        address: self.position,
+      }),
+    }
+  }
+  fn jump_target(&self, p: &ParameterMode<S::Word>) -> Result<usize, IntcodeError<S::Word>> {
+    let target = self.value(p)?;
+    self.to_address(target)
+  }
+  fn to_address(&self, target: S::Word) -> Result<usize, IntcodeError<S::Word>> {
+    let address = self.position;
+    if target < S::Word::default() {
+      return Err(IntcodeError::NegativeAddress { address, target });
+    }
+    match target.to_i64() {
+      Some(i) if i as u64 <= usize::MAX as u64 => Ok(i as usize),
+      _ => Err(IntcodeError::AddressOutOfRange { address, target }),
+    }
+  }
+  fn write(
+    &mut self,
+    p: &ParameterMode<S::Word>,
+    value: S::Word,
+  ) -> Result<(), IntcodeError<S::Word>> {
+    let position = self.destination(p)?;
+    self.write_at(position, value);
+    Ok(())
+  }
+  fn write_at(&mut self, position: usize, value: S::Word) {
+    if let Some(limits) = &mut self.limits {
+      limits.wrote(position, &self.codes[position], &value);
+    }
+    self.codes.set(position, value);
+    if let Some(cache) = &mut self.cache {
+      cache.invalidate(position);
+    }
+  }
+}
+
+impl<W: Word> Program<Queue<W>> {
+  pub fn i(mut self, i: W) -> Self {
+    self.system.input.push_back(i);
+    self
+  }
+}
+
+impl<S: System + Default> From<Vec<S::Word>> for Program<S> {
+  fn from(codes: Vec<S::Word>) -> Self {
+    Self {
+      name: "base".into(),
+      codes: codes.as_slice().into(),
+      original_codes: codes,
+      position: 0,
+      relative_position: S::Word::default(),
+      reset_after_run: false,
+      checked: false,
+      cache: None,
+      profile: None,
+      coverage: None,
+      limits: None,
+      patches: vec![],
+      extensions: BTreeMap::new(),
+      system: S::default(),
+    }
+  }
+}
+
+impl<S: System> Display for Program<S> {
+  /// The current memory as comma separated words, the same way an input file writes them.
+  /// Trailing zeros are left off, so the text does not depend on how far memory has grown, and
+  /// long runs of zeros are shortened as `Memory` prints them.
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    Display::fmt(&self.codes, f)
+  }
+}
+
+impl<S: System + Default> FromStr for Program<S> {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(parse_codes(s)?.into())
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParameterMode<W = I> {
+  Position(W),
+  Immediate(W),
+  Relative(W),
+}
+
+impl<W: Word> ParameterMode<W> {
+  /// The signed address the parameter refers to, or `None` for an immediate.
+  pub fn address(&self, relative_position: &W) -> Option<W> {
+    match self {
+      Self::Position(i) => Some(i.clone()),
+      Self::Immediate(_) => None,
+      Self::Relative(i) => Some(i.wrapping_add(relative_position)),
+    }
+  }
+}
+
+impl<W: Word> ParameterMode<W> {
+  pub fn mode(&self) -> I {
+    match self {
+      Self::Position(_) => 0,
+      Self::Immediate(_) => 1,
+      Self::Relative(_) => 2,
+    }
+  }
+  /// The parameter word as it is stored in the program.
+  pub fn word(&self) -> W {
+    match self {
+      Self::Position(i) | Self::Immediate(i) | Self::Relative(i) => i.clone(),
+    }
+  }
+  pub fn decode(mode: I, value: W) -> Option<Self> {
+    match mode {
+      0 => Some(Self::Position(value)),
+      1 => Some(Self::Immediate(value)),
+      2 => Some(Self::Relative(value)),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OpCode<W = I> {
+  Add(ParameterMode<W>, ParameterMode<W>, ParameterMode<W>),
+  Multiply(ParameterMode<W>, ParameterMode<W>, ParameterMode<W>),
+  Input(ParameterMode<W>),
+  Output(ParameterMode<W>),
+  Break,
+  JumpIfTrue(ParameterMode<W>, ParameterMode<W>),
+  JumpIfFalse(ParameterMode<W>, ParameterMode<W>),
+  LessThan(ParameterMode<W>, ParameterMode<W>, ParameterMode<W>),
+  Equals(ParameterMode<W>, ParameterMode<W>, ParameterMode<W>),
+  AdjustRelativeBase(ParameterMode<W>),
+  /// An instruction registered with `Program::extension`.
+  Custom {
+    code: I,
+    inputs: [Option<ParameterMode<W>>; 3],
+    output: Option<ParameterMode<W>>,
+  },
+}
+
+impl<W: Word> OpCode<W> {
+  /// Decodes the instruction at `address`. Words past the end of `codes` read as zero.
+  pub fn decode(codes: &[W], address: usize) -> Result<Self, IntcodeError<W>> {
+    Self::decode_with(|a| codes.get(a).cloned().unwrap_or_default(), address)
+  }
+  /// Decodes the instruction at `address`, fetching words through `read`.
+  pub fn decode_with(read: impl Fn(usize) -> W, address: usize) -> Result<Self, IntcodeError<W>> {
+    Self::decode_extended(read, address, &BTreeMap::new())
+  }
+  /// Like `decode_with`, but also decodes the opcodes `extensions` has instructions for.
+  pub fn decode_extended(
+    read: impl Fn(usize) -> W,
+    address: usize,
+    extensions: &BTreeMap<I, Extension<W>>,
+  ) -> Result<Self, IntcodeError<W>> {
+    let word = |n: usize| read(address + n);
+    let code = word(0);
+    let i = match code.to_i64() {
+      Some(i) => i,
+      None => return Err(IntcodeError::InvalidOpCode { address, code }),
+    };
+    let param = |n: usize| {
+      let mode = i / [100, 1000, 10000][n - 1] % 10;
+      ParameterMode::decode(mode, word(n)).ok_or(IntcodeError::InvalidMode { address, mode })
+    };
+    let opcode = match i % 100 {
+      1 => Self::Add(param(1)?, param(2)?, param(3)?),
+      2 => Self::Multiply(param(1)?, param(2)?, param(3)?),
+      3 => Self::Input(param(1)?),
+      4 => Self::Output(param(1)?),
+      5 => Self::JumpIfTrue(param(1)?, param(2)?),
+      6 => Self::JumpIfFalse(param(1)?, param(2)?),
+      7 => Self::LessThan(param(1)?, param(2)?, param(3)?),
+      8 => Self::Equals(param(1)?, param(2)?, param(3)?),
+      9 => Self::AdjustRelativeBase(param(1)?),
+      99 => Self::Break,
+      custom => match extensions.get(&custom) {
+        Some(extension) => {
+          let n = extension.inputs;
+          let input = |k: usize| if k <= n { param(k).map(Some) } else { Ok(None) };
+          Self::Custom {
+            code: custom,
+            inputs: [input(1)?, input(2)?, input(3)?],
+            output: match extension.has_output() {
+              true => Some(param(n + 1)?),
+              false => None,
+            },
+          }
+        }
+        None => return Err(IntcodeError::InvalidOpCode { address, code }),
+      },
+    };
+    Ok(opcode)
+  }
+  /// Number of words the instruction takes up, including the opcode itself.
+  pub fn size(&self) -> usize {
+    match self {
+      Self::Add(_, _, _) | Self::Multiply(_, _, _) => 4,
+      Self::LessThan(_, _, _) | Self::Equals(_, _, _) => 4,
+      Self::JumpIfTrue(_, _) | Self::JumpIfFalse(_, _) => 3,
+      Self::Input(_) | Self::Output(_) | Self::AdjustRelativeBase(_) => 2,
+      Self::Break => 1,
+      Self::Custom { inputs, output, .. } => {
+        1 + inputs.iter().flatten().count() + output.iter().count()
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn run_with_input(codes: &str, input: I) -> Vec<I> {
+    let mut program = codes.parse::<Program>().unwrap().i(input);
+    program.run().unwrap();
+    program.system.output
+  }
+
+  #[test]
+  fn test_comparisons() {
+    // Position mode, equal to 8 and less than 8
+    assert_eq!(run_with_input("3,9,8,9,10,9,4,9,99,-1,8", 8), [1]);
+    assert_eq!(run_with_input("3,9,8,9,10,9,4,9,99,-1,8", 7), [0]);
+    assert_eq!(run_with_input("3,9,7,9,10,9,4,9,99,-1,8", 7), [1]);
+    // Immediate mode, equal to 8 and less than 8
+    assert_eq!(run_with_input("3,3,1108,-1,8,3,4,3,99", 8), [1]);
+    assert_eq!(run_with_input("3,3,1107,-1,8,3,4,3,99", 9), [0]);
+  }
+
+  #[test]
+  fn test_jumps() {
+    let position = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+    assert_eq!(run_with_input(position, 0), [0]);
+    assert_eq!(run_with_input(position, 5), [1]);
+    let immediate = "3,3,1105,-1,9,1101,0,0,12,4,12,99,1";
+    assert_eq!(run_with_input(immediate, 0), [0]);
+    assert_eq!(run_with_input(immediate, 5), [1]);
+
+    let larger = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,\
+                  125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+    assert_eq!(run_with_input(larger, 7), [999]);
+    assert_eq!(run_with_input(larger, 8), [1000]);
+    assert_eq!(run_with_input(larger, 9), [1001]);
+  }
+
+  #[test]
+  fn test_relative_base() {
+    let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+    let mut program1 = quine.parse::<Program>().unwrap();
+    program1.run().unwrap();
+    assert_eq!(
+      program1.system.output,
+      [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]
+    );
+
+    let mut program2 = "1102,34915192,34915192,7,4,7,99,0"
+      .parse::<Program>()
+      .unwrap();
+    program2.run().unwrap();
+    assert_eq!(program2.system.output[0].to_string().len(), 16);
+
+    let mut program3 = "104,1125899906842624,99".parse::<Program>().unwrap();
+    program3.run().unwrap();
+    assert_eq!(program3.system.output[0], 1125899906842624);
+
+    let mut program4 = "1101,5,6,1000000000000,4,1000000000000,99"
+      .parse::<Program>()
+      .unwrap();
+    program4.run().unwrap();
+    assert_eq!(program4.system.output, [11]);
+  }
+
+  #[test]
+  fn test_run_to_event() {
+    let mut program = "3,9,4,9,3,9,4,9,99,0".parse::<Program>().unwrap().i(4);
+    assert_eq!(program.run_to_event(), Ok(Event::BreakOnOutput(4)));
+    assert_eq!(program.run_to_event(), Ok(Event::AskForInputAgain));
+    program.system.input.push_back(7);
+    assert_eq!(program.run_to_event(), Ok(Event::BreakOnOutput(7)));
+    assert_eq!(program.run_to_event(), Ok(Event::Halted));
+  }
+
+  #[test]
+  fn test_decode_cache() {
+    // Rewrites the parameter of its first instruction, then loops back to it once.
+    let codes = "104,1,1101,0,5,1,1001,19,1,19,1007,19,2,20,1005,20,0,99,0,0,0";
+    let mut program = codes.parse::<Program>().unwrap().cached();
+    program.run().unwrap();
+    assert_eq!(program.system.output, [1, 5]);
+    assert_eq!(
+      program.cache.as_ref().map(|c| (c.hits, c.misses)),
+      Some((4, 7))
+    );
+    program.reset();
+    program.run().unwrap();
+    assert_eq!(program.system.output, [1, 5]);
+  }
+
+  #[test]
+  fn test_profile() {
+    let mut program = "3,9,4,9,1001,9,-1,9,99,0"
+      .parse::<Program>()
+      .unwrap()
+      .profiled();
+    program.run().unwrap();
+    program.system.input.push_back(2);
+    program.run().unwrap();
+    program.reset();
+    program.system.input.push_back(3);
+    program.run().unwrap();
+    let profile = program.profile.unwrap();
+    let mut hits = profile.hits.iter().collect::<Vec<_>>();
+    hits.sort_unstable();
+    assert_eq!(hits, [(&0, &2), (&2, &2), (&4, &2), (&8, &2)]);
+    // Code far out in memory only adds its own addresses.
+    let far = "1101,0,99,1000000000000,1105,1,1000000000000";
+    let mut jumps = far.parse::<Program>().unwrap().profiled();
+    jumps.run().unwrap();
+    assert_eq!(jumps.profile.unwrap().hits.len(), 3);
+    assert_eq!(
+      (profile.instructions, profile.inputs, profile.outputs),
+      (8, 2, 2)
+    );
+    let report = profile.report(&program.original_codes, 2);
+    assert_eq!(
+      report.lines().collect::<Vec<_>>(),
+      [
+        "instructions: 8 inputs: 2 outputs: 2",
+        "           2  25.00% hlt",
+        "           2  25.00% add",
+        "           2  25.00% in",
+        "           2  25.00% out",
+        "hot addresses:",
+        "           2  25.00%      0: 3,9                      in [9]",
+        "           2  25.00%      2: 4,9                      out [9]",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_word_types() {
+    let square = |n: &str| format!("1102,{0},{0},7,4,7,99,0", n);
+    let small = square("34915192").parse::<Program<Queue<i32>>>().unwrap();
+    let mut wrapping = small.clone();
+    wrapping.run().unwrap();
+    assert_eq!(wrapping.system.output, [34915192i32.wrapping_mul(34915192)]);
+    let mut checked = small.checked();
+    assert_eq!(checked.run(), Err(IntcodeError::Overflow { address: 0 }));
+
+    let mut wide = square("1125899906842624")
+      .parse::<Program<Queue<i128>>>()
+      .unwrap();
+    wide.run().unwrap();
+    assert_eq!(wide.system.output, [1267650600228229401496703205376]);
+    let mut narrow = square("1125899906842624")
+      .parse::<Program>()
+      .unwrap()
+      .checked();
+    assert_eq!(narrow.run(), Err(IntcodeError::Overflow { address: 0 }));
+
+    let adjust = "109,9223372036854775807,109,1,99"
+      .parse::<Program>()
+      .unwrap();
+    assert_eq!(
+      adjust.clone().checked().run(),
+      Err(IntcodeError::Overflow { address: 2 })
+    );
+    assert_eq!(adjust.clone().run(), Ok(None));
+  }
+
+  #[cfg(feature = "bigint")]
+  #[test]
+  fn test_bigint() {
+    use num_bigint::BigInt;
+    let codes = "1102,1267650600228229401496703205376,1267650600228229401496703205376,7,4,7,99,0";
+    let mut program = codes.parse::<Program<Queue<BigInt>>>().unwrap().checked();
+    program.run().unwrap();
+    let expected = "1606938044258990275541962092341162602522202993782792835301376";
+    assert_eq!(program.system.output, [expected.parse::<BigInt>().unwrap()]);
+  }
+
+  #[test]
+  fn test_limits() {
+    // Counts up forever.
+    let counter = "1001,7,1,7,1105,1,0,0";
+    let limits = Limits::default().instructions(100);
+    let mut program = counter.parse::<Program>().unwrap().limited(limits);
+    assert_eq!(
+      program.run(),
+      Err(IntcodeError::BudgetExhausted {
+        address: 0,
+        instructions: 100
+      })
+    );
+    assert_eq!(program.codes[7], 50);
+
+    let limits = Limits::default().time(Duration::from_millis(10));
+    let mut program = counter.parse::<Program>().unwrap().limited(limits);
+    assert!(matches!(program.run(), Err(IntcodeError::TimedOut { .. })));
+
+    // Toggles a cell between 0 and 1 forever, so the third pass repeats the first and the
+    // fifth confirms it.
+    let toggle = "1008,9,0,9,1105,1,0,99,0,0";
+    let limits = Limits::default().detect_loops();
+    let mut program = toggle.parse::<Program>().unwrap().limited(limits.clone());
+    assert_eq!(
+      program.run(),
+      Err(IntcodeError::InfiniteLoop { address: 0 })
+    );
+    assert_eq!(program.limits.as_ref().map(Limits::executed), Some(8));
+    // Spins until another party sets a flag, which is not a loop once the flag is poked.
+    let spin = "1006,5,0,99,0,0";
+    let mut program = spin.parse::<Program>().unwrap().limited(limits.clone());
+    for _ in 0..2 {
+      program.step().unwrap();
+    }
+    program.poke(5, 1);
+    assert_eq!(program.run(), Ok(None));
+    let mut program = counter
+      .parse::<Program>()
+      .unwrap()
+      .limited(limits.instructions(1000));
+    assert!(matches!(
+      program.run(),
+      Err(IntcodeError::BudgetExhausted { .. })
+    ));
+  }
+
+  #[test]
+  fn test_errors() {
+    let error = |codes: &str| codes.parse::<Program>().unwrap().run().unwrap_err();
+    assert_eq!(
+      error("1,0,0,0,42"),
+      IntcodeError::InvalidOpCode {
+        address: 4,
+        code: 42
+      }
+    );
+    assert_eq!(
+      error("1,0,0,0,301,0"),
+      IntcodeError::InvalidMode {
+        address: 4,
+        mode: 3
+      }
+    );
+    assert_eq!(
+      error("109,-5,204,1,99"),
+      IntcodeError::NegativeAddress {
+        address: 2,
+        target: -4
+      }
+    );
+    assert_eq!(
+      error("11101,1,1,0,99"),
+      IntcodeError::WriteToImmediate { address: 0 }
+    );
+    assert_eq!(
+      error("1106,0,-1"),
+      IntcodeError::NegativeAddress {
+        address: 0,
+        target: -1
+      }
+    );
+
+    #[derive(Default)]
+    struct Closed;
+    impl System for Closed {
+      type Word = I;
+      fn send_input(&mut self) -> Event {
+        Event::EndOfInput
+      }
+      fn take_output(&mut self, output: I) -> Event {
+        Event::Output(output)
+      }
+    }
+    let mut program: Program<Closed> = vec![104, 1, 3, 0, 99].into();
+    assert_eq!(
+      program.run(),
+      Err(IntcodeError::InputExhausted { address: 2 })
+    );
+    assert_eq!(program.position, 2);
+  }
+}
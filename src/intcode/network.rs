@@ -0,0 +1,167 @@
+use super::{Event, IntcodeError, Program, Queue, System, Word, I};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+/// A `System` for a program running on its own thread. Input blocks on a channel and every
+/// output is recorded and sent to each connected machine.
+#[derive(Debug)]
+pub struct Channels<W = I> {
+  pub input: Receiver<W>,
+  pub outputs: Vec<Sender<W>>,
+  pub output: Vec<W>,
+}
+
+impl<W: Word> System for Channels<W> {
+  type Word = W;
+
+  fn send_input(&mut self) -> Event<W> {
+    match self.input.recv() {
+      Ok(i) => Event::Input(i),
+      Err(_) => Event::EndOfInput,
+    }
+  }
+
+  fn take_output(&mut self, output: W) -> Event<W> {
+    self.output.push(output.clone());
+    // A machine that already halted no longer listens, which is fine.
+    self
+      .outputs
+      .iter()
+      .for_each(|tx| tx.send(output.clone()).unwrap_or(()));
+    Event::Output(output)
+  }
+}
+
+/// Wires programs together with channels and runs each on its own thread.
+///
+/// Inputs already queued on a program are sent ahead of anything its upstream machines
+/// produce, so phase settings and seed values go in with `Program::i`.
+pub struct Network<W: Word = I> {
+  programs: Vec<Program<Queue<W>>>,
+  edges: Vec<(usize, usize)>,
+}
+
+impl<W: Word> Default for Network<W> {
+  fn default() -> Self {
+    Self {
+      programs: vec![],
+      edges: vec![],
+    }
+  }
+}
+
+impl<W: Word> Network<W> {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Each program feeds the next one.
+  pub fn chain(programs: impl IntoIterator<Item = Program<Queue<W>>>) -> Self {
+    let mut network = Self::new();
+    for program in programs {
+      let machine = network.machine(program);
+      if machine > 0 {
+        network.connect(machine - 1, machine);
+      }
+    }
+    network
+  }
+  /// A chain whose last program feeds the first.
+  pub fn ring(programs: impl IntoIterator<Item = Program<Queue<W>>>) -> Self {
+    let mut network = Self::chain(programs);
+    if let Some(last) = network.programs.len().checked_sub(1) {
+      network.connect(last, 0);
+    }
+    network
+  }
+  /// Adds a program, returning its index.
+  pub fn machine(&mut self, program: Program<Queue<W>>) -> usize {
+    self.programs.push(program);
+    self.programs.len() - 1
+  }
+  /// Sends every output of machine `from` to machine `to`.
+  pub fn connect(&mut self, from: usize, to: usize) -> &mut Self {
+    self.edges.push((from, to));
+    self
+  }
+  /// Runs every machine until it halts or its inputs are all closed, returning the programs
+  /// in the order they were added.
+  pub fn run(self) -> Result<Vec<Program<Channels<W>>>, IntcodeError<W>> {
+    let Self { programs, edges } = self;
+    let (senders, receivers): (Vec<_>, Vec<_>) = programs.iter().map(|_| channel::<W>()).unzip();
+    // Seed every channel before any machine starts, so queued inputs come first.
+    let programs = programs
+      .into_iter()
+      .zip(&senders)
+      .map(|(mut program, tx)| {
+        program
+          .system
+          .input
+          .drain(..)
+          .for_each(|i| tx.send(i).unwrap());
+        program
+      })
+      .collect::<Vec<_>>();
+    let handles = programs
+      .into_iter()
+      .zip(receivers)
+      .enumerate()
+      .map(|(n, (program, input))| {
+        let outputs = edges
+          .iter()
+          .filter(|(from, _)| *from == n)
+          .map(|(_, to)| senders[*to].clone())
+          .collect();
+        let mut program = program.with_system(Channels {
+          input,
+          outputs,
+          output: vec![],
+        });
+        thread::spawn(move || {
+          let result = program.run();
+          // Close the outputs so machines downstream see the end of their input.
+          program.system.outputs.clear();
+          result.map(|_| program)
+        })
+      })
+      .collect::<Vec<_>>();
+    drop(senders);
+    handles
+      .into_iter()
+      .map(|handle| handle.join().unwrap())
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_network() {
+    let amplifier = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,\
+                     1005,28,6,99,0,0,5"
+      .parse::<Program>()
+      .unwrap();
+    let phases = [9, 8, 7, 6, 5];
+    let programs = phases.iter().enumerate().map(|(n, phase)| {
+      let program = amplifier.clone().i(*phase);
+      if n == 0 {
+        program.i(0)
+      } else {
+        program
+      }
+    });
+    let programs = Network::ring(programs).run().unwrap();
+    assert_eq!(programs[4].system.output.last(), Some(&139629729));
+
+    let doubler = "3,9,102,2,9,9,4,9,99,0".parse::<Program>().unwrap();
+    let mut network = Network::new();
+    let a = network.machine(doubler.clone().i(3));
+    let b = network.machine(doubler.clone());
+    let c = network.machine(doubler);
+    network.connect(a, b).connect(a, c);
+    let programs = network.run().unwrap();
+    assert_eq!(programs[b].system.output, [12]);
+    assert_eq!(programs[c].system.output, [12]);
+  }
+}
@@ -0,0 +1,78 @@
+use super::{Event, IntcodeError, Program, Queue, Word};
+
+/// Iterator over a program's outputs, returned by `Program::outputs`.
+pub struct Outputs<'a, W: Word, In> {
+  program: &'a mut Program<Queue<W>>,
+  inputs: In,
+  done: bool,
+}
+
+impl<W: Word> Program<Queue<W>> {
+  /// Runs the program lazily, yielding each output as soon as it is produced. Inputs already
+  /// queued go first, then `inputs` is drawn from one value at a time as the program asks.
+  ///
+  /// The iterator ends when the program halts or asks for input once `inputs` has run out. In
+  /// the second case the program waits on its input instruction, so more can be queued or
+  /// another iterator started to carry on.
+  pub fn outputs<In: IntoIterator<Item = W>>(
+    &mut self,
+    inputs: In,
+  ) -> Outputs<'_, W, In::IntoIter> {
+    Outputs {
+      program: self,
+      inputs: inputs.into_iter(),
+      done: false,
+    }
+  }
+}
+
+impl<W: Word, In: Iterator<Item = W>> Iterator for Outputs<'_, W, In> {
+  type Item = Result<W, IntcodeError<W>>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.done {
+      match self.program.step() {
+        Ok(Some(Event::Output(_))) | Ok(Some(Event::BreakOnOutput(_))) => {
+          return self.program.system.output.pop().map(Ok)
+        }
+        Ok(Some(Event::AskForInputAgain)) => match self.inputs.next() {
+          Some(input) => self.program.system.input.push_back(input),
+          None => self.done = true,
+        },
+        Ok(Some(Event::Halted)) => self.done = true,
+        Ok(_) => {}
+        Err(e) => {
+          self.done = true;
+          return Some(Err(e));
+        }
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::intcode::{Program, I};
+
+  #[test]
+  fn test_outputs() {
+    // Outputs each input doubled, forever.
+    let mut program = "3,11,102,2,11,11,4,11,1105,1,0,0"
+      .parse::<Program>()
+      .unwrap();
+    let mut outputs = program.outputs(1..);
+    assert_eq!(outputs.next(), Some(Ok(2)));
+    assert_eq!(outputs.next(), Some(Ok(4)));
+    // A second iterator carries on where the first stopped.
+    let doubled = program.outputs(vec![10, 20]).collect::<Result<Vec<I>, _>>();
+    assert_eq!(doubled, Ok(vec![20, 40]));
+    assert_eq!(program.position, 0);
+    assert!(program.system.output.is_empty());
+
+    let mut halts = "104,7,99".parse::<Program>().unwrap();
+    assert_eq!(halts.outputs(None).collect::<Vec<_>>(), [Ok(7)]);
+    let error = "104,7,42".parse::<Program>().unwrap().outputs(None).nth(1);
+    assert!(matches!(error, Some(Err(_))));
+  }
+}
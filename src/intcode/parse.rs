@@ -0,0 +1,104 @@
+use super::Word;
+use std::fmt::{Display, Error, Formatter};
+
+/// A word of program text that does not parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+  /// Position of the word among the comma separated ones.
+  pub index: usize,
+  /// Byte offset of the word in the text.
+  pub offset: usize,
+  pub text: String,
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    write!(
+      f,
+      "invalid word {:?} at index {} (byte {})",
+      self.text, self.index, self.offset
+    )
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses comma separated words, allowing any whitespace, newlines included, around each one.
+/// Text with nothing but whitespace is an empty program.
+pub fn parse_codes<W: Word>(s: &str) -> Result<Vec<W>, ParseError> {
+  if s.trim().is_empty() {
+    return Ok(vec![]);
+  }
+  let mut offset = 0;
+  let mut codes = vec![];
+  for (index, token) in s.split(',').enumerate() {
+    let text = token.trim();
+    let start = offset + token.len() - token.trim_start().len();
+    offset += token.len() + 1;
+    match text.parse() {
+      Ok(word) => codes.push(word),
+      Err(_) => {
+        return Err(ParseError {
+          index,
+          offset: start,
+          text: text.into(),
+        })
+      }
+    }
+  }
+  Ok(codes)
+}
+
+/// Like `parse_codes`, but `;` starts a comment that runs to the end of the line, for
+/// program files annotated by hand.
+pub fn parse_annotated<W: Word>(s: &str) -> Result<Vec<W>, ParseError> {
+  // Blanking comments out keeps the offsets of everything else.
+  let mut comment = false;
+  let mut text = String::with_capacity(s.len());
+  for c in s.chars() {
+    comment = (comment || c == ';') && c != '\n';
+    if comment {
+      text.push_str(&" ".repeat(c.len_utf8()));
+    } else {
+      text.push(c);
+    }
+  }
+  parse_codes(&text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::I;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      parse_codes::<I>("1,0, 0,\n0 ,99\n"),
+      Ok(vec![1, 0, 0, 0, 99])
+    );
+    assert_eq!(parse_codes::<I>(" \n"), Ok(vec![]));
+    let error = |index, offset, text: &str| {
+      Err(ParseError {
+        index,
+        offset,
+        text: text.into(),
+      })
+    };
+    assert_eq!(parse_codes::<I>("1,2,x3,4"), error(2, 4, "x3"));
+    assert_eq!(parse_codes::<I>("1,\n  2 3"), error(1, 5, "2 3"));
+    assert_eq!(parse_codes::<I>("1,2,"), error(2, 4, ""));
+    assert_eq!(
+      parse_codes::<I>("1,;c").unwrap_err().to_string(),
+      "invalid word \";c\" at index 1 (byte 2)"
+    );
+
+    let annotated = "; doubles its input\n3,9, ; in [9]\n102,2,9,9,4,9,99,0 ;x,y\n";
+    assert_eq!(
+      parse_annotated::<I>(annotated),
+      Ok(vec![3, 9, 102, 2, 9, 9, 4, 9, 99, 0])
+    );
+    assert_eq!(parse_annotated::<I>("1,\n; 2,\nz"), error(1, 8, "z"));
+    assert_eq!(parse_annotated::<I>("; é\nq"), error(0, 5, "q"));
+  }
+}
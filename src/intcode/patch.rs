@@ -0,0 +1,157 @@
+use super::{Memory, Program, System, Word, I};
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A change to one word of a program's image, written `address=value` with an optional name
+/// after it, such as `0=2 free play`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch<W = I> {
+  pub address: usize,
+  pub value: W,
+  pub name: Option<String>,
+}
+
+impl<W: Word> Patch<W> {
+  pub fn new(address: usize, value: W) -> Self {
+    Self {
+      address,
+      value,
+      name: None,
+    }
+  }
+  pub fn named(mut self, name: impl Into<String>) -> Self {
+    self.name = Some(name.into());
+    self
+  }
+  /// Reads a patch file: one patch per line, with blank lines and `#` comments ignored.
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Self>> {
+    parse_patches(&fs::read_to_string(path)?)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+pub fn parse_patches<W: Word>(s: &str) -> Result<Vec<Patch<W>>, String> {
+  s.lines()
+    .enumerate()
+    .map(|(n, line)| (n, line.split('#').next().unwrap().trim()))
+    .filter(|(_, line)| !line.is_empty())
+    .map(|(n, line)| line.parse().map_err(|e| format!("line {}: {}", n + 1, e)))
+    .collect()
+}
+
+impl<S: System> Program<S> {
+  /// Changes the program's image, so the patch survives `reset`, and remembers it in `patches`.
+  /// A patch past the end of `original_codes` leaves it as it is, so patching far out in memory
+  /// costs no more than writing there.
+  pub fn patch(mut self, patch: Patch<S::Word>) -> Self {
+    if let Some(word) = self.original_codes.get_mut(patch.address) {
+      *word = patch.value.clone();
+    }
+    self.write_at(patch.address, patch.value.clone());
+    self.patches.push(patch);
+    self
+  }
+  pub fn patched(self, patches: impl IntoIterator<Item = Patch<S::Word>>) -> Self {
+    patches.into_iter().fold(self, Self::patch)
+  }
+  /// The memory a reset starts from: `original_codes` with the patches past its end applied.
+  pub fn image(&self) -> Memory<S::Word> {
+    let mut image = Memory::from(self.original_codes.as_slice());
+    for patch in &self.patches {
+      if patch.address >= self.original_codes.len() {
+        image.set(patch.address, patch.value.clone());
+      }
+    }
+    image
+  }
+}
+
+impl<W: Word> Display for Patch<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    write!(f, "{}={}", self.address, self.value)?;
+    match &self.name {
+      Some(name) => write!(f, " {}", name),
+      None => Ok(()),
+    }
+  }
+}
+
+impl<W: Word> FromStr for Patch<W> {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    let (assignment, name) = match s.split_once(char::is_whitespace) {
+      Some((assignment, name)) => (assignment, Some(name.trim().to_string())),
+      None => (s, None),
+    };
+    let (address, value) = assignment
+      .split_once('=')
+      .ok_or_else(|| format!("expected address=value, found '{}'", s))?;
+    // Programs address memory with words, so nothing past `i64::MAX` can be reached.
+    let address = address
+      .parse()
+      .ok()
+      .filter(|&a| i64::try_from(a).is_ok())
+      .ok_or_else(|| format!("invalid address '{}'", address))?;
+    Ok(Self {
+      address,
+      value: value
+        .parse()
+        .map_err(|_| format!("invalid value '{}'", value))?,
+      name,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_patches() {
+    let patches = parse_patches::<I>("# day 2\n1=12 noun\n2=2   verb\n\n5=0\n").unwrap();
+    assert_eq!(
+      patches,
+      [
+        Patch::new(1, 12).named("noun"),
+        Patch::new(2, 2).named("verb"),
+        Patch::new(5, 0)
+      ]
+    );
+    assert_eq!(patches[0].to_string(), "1=12 noun");
+    assert_eq!(patches[2].to_string(), "5=0");
+    assert_eq!(
+      parse_patches::<I>("1=12\n2:2"),
+      Err("line 2: expected address=value, found '2:2'".to_string())
+    );
+    assert_eq!(
+      parse_patches::<I>("x=1"),
+      Err("line 1: invalid address 'x'".to_string())
+    );
+
+    let mut program = "1,0,0,0,99".parse::<Program>().unwrap().patched(patches);
+    assert_eq!(program.original_codes, [1, 12, 2, 0, 99]);
+    program.run().unwrap();
+    assert_eq!(program.codes[0], 2);
+    program.reset();
+    assert_eq!((program.codes[1], program.codes.len()), (12, 6));
+    assert_eq!(program.patches.len(), 3);
+
+    // Patches far out in memory stay sparse and survive a reset.
+    let far = Patch::new(1_000_000_000_000, 7);
+    let mut program = "99".parse::<Program>().unwrap().patch(far);
+    assert_eq!(program.original_codes, [99]);
+    program.codes.set(1_000_000_000_000, 0);
+    program.reset();
+    assert_eq!(program.codes[1_000_000_000_000], 7);
+    assert_eq!(
+      parse_patches::<I>("9223372036854775808=1"),
+      Err("line 1: invalid address '9223372036854775808'".to_string())
+    );
+  }
+}
@@ -0,0 +1,98 @@
+use super::{Line, OpCode, Word};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const MNEMONICS: [&str; 10] = [
+  "hlt", "add", "mul", "in", "out", "jt", "jf", "lt", "eq", "arb",
+];
+
+/// Execution counts gathered while a program runs. It survives `Program::reset`, so repeated
+/// runs add up.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+  /// Times the instruction at each address executed, for the addresses that did.
+  pub hits: HashMap<usize, u64>,
+  /// Executed instructions by opcode, indexed by `OpCode::code() % 99`.
+  pub opcodes: [u64; 10],
+  /// Executed instructions registered as extensions.
+  pub extensions: u64,
+  pub inputs: u64,
+  pub outputs: u64,
+  pub instructions: u64,
+}
+
+impl Profile {
+  pub fn record<W: Word>(&mut self, address: usize, opcode: &OpCode<W>) {
+    *self.hits.entry(address).or_insert(0) += 1;
+    self.instructions += 1;
+    match opcode {
+      OpCode::Input(_) => self.inputs += 1,
+      OpCode::Output(_) => self.outputs += 1,
+      OpCode::Custom { .. } => {
+        self.extensions += 1;
+        return;
+      }
+      _ => {}
+    }
+    self.opcodes[(opcode.code() % 99) as usize] += 1;
+  }
+  /// Takes back a `record`, for a step that was undone.
+  pub fn unrecord<W: Word>(&mut self, address: usize, opcode: &OpCode<W>) {
+    if let Some(hits) = self.hits.get_mut(&address) {
+      *hits -= 1;
+      if *hits == 0 {
+        self.hits.remove(&address);
+      }
+    }
+    self.instructions -= 1;
+    match opcode {
+      OpCode::Input(_) => self.inputs -= 1,
+      OpCode::Output(_) => self.outputs -= 1,
+      OpCode::Custom { .. } => {
+        self.extensions -= 1;
+        return;
+      }
+      _ => {}
+    }
+    self.opcodes[(opcode.code() % 99) as usize] -= 1;
+  }
+  /// Totals, the opcode mix and the `top` hottest addresses disassembled from `codes`.
+  pub fn report<W: Word>(&self, codes: &[W], top: usize) -> String {
+    let percent = |n: u64| 100.0 * n as f64 / self.instructions.max(1) as f64;
+    let mut report = String::new();
+    writeln!(
+      report,
+      "instructions: {} inputs: {} outputs: {}",
+      self.instructions, self.inputs, self.outputs
+    )
+    .unwrap();
+    let mut opcodes = MNEMONICS
+      .iter()
+      .zip(&self.opcodes)
+      .chain(Some((&"op", &self.extensions)))
+      .collect::<Vec<_>>();
+    opcodes.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+    for (mnemonic, count) in opcodes.into_iter().filter(|(_, count)| **count > 0) {
+      writeln!(
+        report,
+        "{:>12} {:>6.2}% {}",
+        count,
+        percent(*count),
+        mnemonic
+      )
+      .unwrap();
+    }
+    let mut hot = self.hits.iter().collect::<Vec<_>>();
+    hot.sort_by_key(|(address, count)| (std::cmp::Reverse(**count), **address));
+    writeln!(report, "hot addresses:").unwrap();
+    for (address, count) in hot.into_iter().take(top) {
+      let line = if *address < codes.len() {
+        Line::at(codes, *address).to_string()
+      } else {
+        format!("{:>6}: (outside the program)", address)
+      };
+      writeln!(report, "{:>12} {:>6.2}% {}", count, percent(*count), line).unwrap();
+    }
+    report
+  }
+}
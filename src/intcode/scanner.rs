@@ -0,0 +1,135 @@
+use super::{Memory, Patch, Program, System, Word, I};
+use std::collections::BTreeMap;
+
+/// How a candidate's value has to relate to the value it held at the previous scan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Scan<W = I> {
+  Equals(W),
+  Changed,
+  Unchanged,
+  Increased,
+  Decreased,
+  /// Changed by exactly this much, such as the distance an observed sprite moved.
+  ChangedBy(W),
+}
+
+impl<W: Word> Scan<W> {
+  pub fn matches(&self, old: &W, new: &W) -> bool {
+    match self {
+      Self::Equals(value) => new == value,
+      Self::Changed => new != old,
+      Self::Unchanged => new == old,
+      Self::Increased => new > old,
+      Self::Decreased => new < old,
+      Self::ChangedBy(delta) => *new == old.wrapping_add(delta),
+    }
+  }
+}
+
+/// Finds where a running program keeps a piece of state, the way a game cheat tool does:
+/// start with every address, then after each frame keep only those whose value tracks
+/// something observed from the outside, such as a score that was just printed. Whatever is
+/// found can then be pinned to a value or patched into the image.
+#[derive(Clone, Debug, Default)]
+pub struct Scanner<W = I> {
+  /// Addresses still in the running, with the value each held at the last scan.
+  pub candidates: BTreeMap<usize, W>,
+  /// Values `hold` writes back into the program.
+  pub pinned: BTreeMap<usize, W>,
+}
+
+impl<W: Word> Scanner<W> {
+  /// Starts with every address of `codes` as a candidate. Addresses on pages that were never
+  /// allocated are left out, since nothing has been stored there yet.
+  pub fn new(codes: &Memory<W>) -> Self {
+    let words = codes.pages().into_iter().flat_map(|(start, words)| {
+      let words = words.iter().cloned().enumerate();
+      words.map(move |(i, word)| (start + i, word))
+    });
+    Self {
+      candidates: words.take_while(|(a, _)| *a < codes.len()).collect(),
+      pinned: BTreeMap::new(),
+    }
+  }
+  /// Keeps the candidates whose value in `codes` passes `scan`, and returns how many are left.
+  pub fn scan(&mut self, codes: &Memory<W>, scan: &Scan<W>) -> usize {
+    self.candidates.retain(|&address, old| {
+      let new = codes.get(address);
+      let keep = scan.matches(old, &new);
+      *old = new;
+      keep
+    });
+    self.candidates.len()
+  }
+  pub fn pin(&mut self, address: usize, value: W) {
+    self.pinned.insert(address, value);
+  }
+  pub fn unpin(&mut self, address: usize) {
+    self.pinned.remove(&address);
+  }
+  /// Writes every pinned value back into `program`. Call it once a frame to hold them.
+  pub fn hold<S: System<Word = W>>(&self, program: &mut Program<S>) {
+    for (address, value) in &self.pinned {
+      program.poke(*address, value.clone());
+    }
+  }
+  /// The pinned values as patches, to bake them into an image with `Program::patched`.
+  pub fn patches(&self) -> Vec<Patch<W>> {
+    self
+      .pinned
+      .iter()
+      .map(|(address, value)| Patch::new(*address, value.clone()).named("pinned"))
+      .collect()
+  }
+}
+
+impl<S: System> Program<S> {
+  /// Overwrites a word of memory from outside the program.
+  pub fn poke(&mut self, address: usize, value: S::Word) {
+    self.write_at(address, value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::{Event, PAGE_SIZE};
+
+  #[test]
+  fn test_scanner() {
+    // Adds each input to a score at 21 and takes a life from 22, then outputs the score.
+    let codes: Vec<I> = vec![
+      3, 20, 1, 20, 21, 21, 1001, 22, -1, 22, 4, 21, 1105, 1, 0, 0, 0, 0, 0, 0, 0, 0, 3,
+    ];
+    let mut program = Program::from(codes).cached();
+    let mut score = Scanner::new(&program.codes);
+    let mut lives = score.clone();
+    let frame = |program: &mut Program, input| {
+      program.system.input.push_back(input);
+      program.run_to_event().unwrap()
+    };
+    assert_eq!(frame(&mut program, 5), Event::BreakOnOutput(5));
+    assert_eq!(score.scan(&program.codes, &Scan::Equals(5)), 2);
+    assert_eq!(lives.scan(&program.codes, &Scan::Decreased), 1);
+    frame(&mut program, 0);
+    assert_eq!(score.scan(&program.codes, &Scan::Equals(5)), 1);
+    assert_eq!(lives.scan(&program.codes, &Scan::ChangedBy(-1)), 1);
+    assert_eq!(score.candidates.keys().collect::<Vec<_>>(), [&21]);
+    assert_eq!(lives.candidates.keys().collect::<Vec<_>>(), [&22]);
+
+    lives.pin(22, 9);
+    lives.hold(&mut program);
+    frame(&mut program, 1);
+    assert_eq!(program.codes.get(22), 8);
+    lives.hold(&mut program);
+    assert_eq!(program.codes.get(22), 9);
+    assert_eq!(lives.patches(), [Patch::new(22, 9).named("pinned")]);
+
+    // A write far past the image only adds the page it landed on.
+    program.poke(1 << 40, 7);
+    let mut far = Scanner::new(&program.codes);
+    assert!(far.candidates.len() <= 2 * PAGE_SIZE);
+    assert_eq!(far.scan(&program.codes, &Scan::Equals(7)), 1);
+    assert_eq!(far.candidates.keys().collect::<Vec<_>>(), [&(1 << 40)]);
+  }
+}
@@ -0,0 +1,183 @@
+use super::{Memory, Program, System, Word, I};
+use std::fmt::{Display, Error, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+const HEADER: &str = "intcode-snapshot 1";
+
+/// The full state of a `Program`: registers, every non-zero page of memory and whatever the
+/// `System` chooses to save.
+///
+/// Written as a small text file: a header line, `position`, `relative_position`, `len` and
+/// `system` lines, and one `page <start> <words>` line per page with its trailing zeros trimmed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot<W = I> {
+  pub position: usize,
+  pub relative_position: W,
+  /// The memory's `len`, which may reach past its last non-zero word.
+  pub len: usize,
+  pub pages: Vec<(usize, Vec<W>)>,
+  pub system: Vec<W>,
+}
+
+impl<S: System> Program<S> {
+  pub fn snapshot(&self) -> Snapshot<S::Word> {
+    let pages = self
+      .codes
+      .pages()
+      .into_iter()
+      .filter_map(|(start, words)| {
+        let used = words.iter().rposition(|w| !w.is_zero())? + 1;
+        Some((start, words[..used].to_vec()))
+      })
+      .collect();
+    Snapshot {
+      position: self.position,
+      relative_position: self.relative_position.clone(),
+      len: self.codes.len(),
+      pages,
+      system: self.system.save_state(),
+    }
+  }
+  /// Puts the program back in the state `snapshot` was taken in.
+  pub fn restore(&mut self, snapshot: &Snapshot<S::Word>) {
+    self.position = snapshot.position;
+    self.relative_position = snapshot.relative_position.clone();
+    self.codes = snapshot.memory();
+    if let Some(cache) = &mut self.cache {
+      cache.clear();
+    }
+    if let Some(limits) = &mut self.limits {
+      limits.restart();
+    }
+    self.system.restore_state(&snapshot.system);
+  }
+}
+
+impl<W: Word> Snapshot<W> {
+  /// The program's memory when the snapshot was taken.
+  pub fn memory(&self) -> Memory<W> {
+    let mut memory = Memory::default();
+    if let Some(last) = self.len.checked_sub(1) {
+      memory.set(last, W::default());
+    }
+    for (start, words) in &self.pages {
+      for (n, word) in words.iter().enumerate() {
+        memory.set(start + n, word.clone());
+      }
+    }
+    memory
+  }
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, self.to_string())
+  }
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+    fs::read_to_string(path)?
+      .parse()
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+fn join<W: Word>(words: &[W]) -> String {
+  words
+    .iter()
+    .map(|w| w.to_string())
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+impl<W: Word> Display for Snapshot<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    writeln!(f, "{}", HEADER)?;
+    writeln!(f, "position {}", self.position)?;
+    writeln!(f, "relative_position {}", self.relative_position)?;
+    writeln!(f, "len {}", self.len)?;
+    writeln!(f, "system {}", join(&self.system))?;
+    for (start, words) in &self.pages {
+      writeln!(f, "page {} {}", start, join(words))?;
+    }
+    Ok(())
+  }
+}
+
+impl<W: Word> FromStr for Snapshot<W> {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut lines = s.lines();
+    if lines.next() != Some(HEADER) {
+      return Err("not an intcode snapshot".into());
+    }
+    let number = |s: &str| {
+      s.trim()
+        .parse::<W>()
+        .map_err(|_| format!("invalid number '{}'", s))
+    };
+    let address = |s: &str| {
+      s.trim()
+        .parse::<usize>()
+        .map_err(|_| format!("invalid address '{}'", s))
+    };
+    let words = |s: &str| {
+      s.split(',')
+        .filter(|w| !w.trim().is_empty())
+        .map(number)
+        .collect::<Result<Vec<_>, _>>()
+    };
+    let mut snapshot = Self::default();
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+      let mut fields = line.splitn(3, ' ');
+      match (fields.next(), fields.next(), fields.next()) {
+        (Some("position"), Some(p), None) => snapshot.position = address(p)?,
+        (Some("relative_position"), Some(rb), None) => snapshot.relative_position = number(rb)?,
+        (Some("len"), Some(len), None) => snapshot.len = address(len)?,
+        (Some("system"), rest, None) => snapshot.system = words(rest.unwrap_or(""))?,
+        (Some("page"), Some(start), rest) => snapshot
+          .pages
+          .push((address(start)?, words(rest.unwrap_or(""))?)),
+        _ => return Err(format!("invalid line '{}'", line)),
+      }
+    }
+    Ok(snapshot)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::Event;
+
+  #[test]
+  fn test_snapshot() {
+    let codes = "3,20,1001,20,1,20,4,20,1105,1,0";
+    let mut program = codes.parse::<Program>().unwrap().i(1);
+    assert_eq!(program.run_to_event(), Ok(Event::BreakOnOutput(2)));
+    program.system.input.push_back(5);
+    let snapshot = program.snapshot();
+    let text = snapshot.to_string();
+    assert_eq!(
+      text,
+      "intcode-snapshot 1\nposition 8\nrelative_position 0\nlen 21\nsystem 1,5,2\n\
+       page 0 3,20,1001,20,1,20,4,20,1105,1,0,0,0,0,0,0,0,0,0,0,2\n"
+    );
+    assert_eq!(text.parse(), Ok(snapshot.clone()));
+
+    program.run_to_event().unwrap();
+    assert_eq!(program.system.output, [2, 6]);
+    let mut restored = codes.parse::<Program>().unwrap();
+    restored.restore(&snapshot);
+    restored.run_to_event().unwrap();
+    assert_eq!(restored.system.output, [2, 6]);
+    assert_eq!(restored.codes, program.codes);
+
+    // Writing a zero past the end grows memory without allocating a page for it.
+    let mut program = "1101,0,0,100,99".parse::<Program>().unwrap();
+    program.run().unwrap();
+    let snapshot = program.snapshot();
+    assert_eq!(snapshot.len, 101);
+    assert_eq!(snapshot.to_string().parse(), Ok(snapshot.clone()));
+    assert_eq!(snapshot.memory().len(), 101);
+  }
+}
@@ -0,0 +1,975 @@
+use super::{IntcodeError, OpCode, ParameterMode, Word, I};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::rc::Rc;
+
+/// How many times narrowing goes over a path's conditions before it settles for the bounds it
+/// has.
+const NARROWING_ROUNDS: usize = 8;
+
+/// A factor of a term in an `Expr`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Atom<W = I> {
+  /// The nth input, counting from zero.
+  Input(usize),
+  /// 1 if the expression is below zero, 0 otherwise.
+  Negative(Rc<Expr<W>>),
+  /// 1 if the expression is zero, 0 otherwise.
+  Zero(Rc<Expr<W>>),
+}
+
+/// A value computed from a program's inputs, kept as a sum of terms so that equal values
+/// always look the same, however the program got to them.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Expr<W = I> {
+  /// Coefficients of each product of atoms. The empty product is the constant term.
+  terms: BTreeMap<Vec<Atom<W>>, W>,
+}
+
+impl<W: Word> Expr<W> {
+  fn zero() -> Self {
+    Self {
+      terms: BTreeMap::new(),
+    }
+  }
+  pub fn constant(w: W) -> Self {
+    Self::term(vec![], w)
+  }
+  pub fn input(n: usize) -> Self {
+    Self::term(vec![Atom::Input(n)], W::from_i64(1))
+  }
+  fn term(atoms: Vec<Atom<W>>, coefficient: W) -> Self {
+    let mut terms = BTreeMap::new();
+    if !coefficient.is_zero() {
+      terms.insert(atoms, coefficient);
+    }
+    Self { terms }
+  }
+  /// The value if it does not depend on any input.
+  pub fn as_constant(&self) -> Option<W> {
+    match self.terms.iter().next() {
+      None => Some(W::default()),
+      Some((atoms, w)) if atoms.is_empty() && self.terms.len() == 1 => Some(w.clone()),
+      _ => None,
+    }
+  }
+  pub fn sum(mut a: Self, b: Self) -> Self {
+    for (atoms, w) in b.terms {
+      let sum = match a.terms.remove(&atoms) {
+        Some(v) => v.wrapping_add(&w),
+        None => w,
+      };
+      if !sum.is_zero() {
+        a.terms.insert(atoms, sum);
+      }
+    }
+    a
+  }
+  pub fn product(a: Self, b: Self) -> Self {
+    let mut product = Self::zero();
+    for (x, v) in &a.terms {
+      for (y, w) in &b.terms {
+        let mut atoms = x.clone();
+        // Comparisons are 0 or 1, so squaring one changes nothing.
+        for atom in y {
+          if matches!(atom, Atom::Input(_)) || !atoms.contains(atom) {
+            atoms.push(atom.clone());
+          }
+        }
+        atoms.sort();
+        product = Self::sum(product, Self::term(atoms, v.wrapping_mul(w)));
+      }
+    }
+    product
+  }
+  fn negate(self) -> Self {
+    Self::product(self, Self::constant(W::from_i64(-1)))
+  }
+  pub fn less_than(a: Self, b: Self) -> Self {
+    let difference = Self::sum(a, b.negate());
+    match difference.as_constant() {
+      Some(d) => Self::constant(W::from_i64((d < W::default()) as i64)),
+      None => Self::term(vec![Atom::Negative(Rc::new(difference))], W::from_i64(1)),
+    }
+  }
+  pub fn equals(a: Self, b: Self) -> Self {
+    let mut difference = Self::sum(a, b.negate());
+    if let Some(d) = difference.as_constant() {
+      return Self::constant(W::from_i64(d.is_zero() as i64));
+    }
+    // a - b and b - a are zero together, so keep whichever starts with a positive term.
+    if difference.terms.values().next().unwrap() < &W::default() {
+      difference = difference.negate();
+    }
+    Self::term(vec![Atom::Zero(Rc::new(difference))], W::from_i64(1))
+  }
+  /// The value with concrete `inputs` substituted. Missing inputs read as zero.
+  pub fn eval(&self, inputs: &[W]) -> W {
+    let atom = |atom: &Atom<W>| match atom {
+      Atom::Input(n) => inputs.get(*n).cloned().unwrap_or_default(),
+      Atom::Negative(e) => W::from_i64((e.eval(inputs) < W::default()) as i64),
+      Atom::Zero(e) => W::from_i64(e.eval(inputs).is_zero() as i64),
+    };
+    self.terms.iter().fold(W::default(), |sum, (atoms, w)| {
+      let term = atoms
+        .iter()
+        .fold(w.clone(), |p, a| p.wrapping_mul(&atom(a)));
+      sum.wrapping_add(&term)
+    })
+  }
+  /// The terms with positive and with negative coefficients, as two expressions that are both
+  /// written without a leading minus sign.
+  fn sides(&self) -> (Self, Self) {
+    let (mut positive, mut negative) = (Self::zero(), Self::zero());
+    for (atoms, w) in &self.terms {
+      if *w < W::default() {
+        let term = Self::term(atoms.clone(), w.clone()).negate();
+        negative = Self::sum(negative, term);
+      } else {
+        positive = Self::sum(positive, Self::term(atoms.clone(), w.clone()));
+      }
+    }
+    (positive, negative)
+  }
+  /// A single comparison atom, if that is all the expression is.
+  fn comparison(&self) -> Option<&Atom<W>> {
+    match self.terms.iter().next() {
+      Some((atoms, w)) if self.terms.len() == 1 && atoms.len() == 1 => {
+        Some(&atoms[0]).filter(|a| *w == W::from_i64(1) && !matches!(a, Atom::Input(_)))
+      }
+      _ => None,
+    }
+  }
+  /// The inputs the expression depends on, including through comparisons.
+  fn inputs(&self) -> BTreeSet<usize> {
+    let mut inputs = BTreeSet::new();
+    for atom in self.terms.keys().flatten() {
+      match atom {
+        Atom::Input(n) => {
+          inputs.insert(*n);
+        }
+        Atom::Negative(e) | Atom::Zero(e) => inputs.extend(e.inputs()),
+      }
+    }
+    inputs
+  }
+  /// The coefficient of input `n` and everything else, if the expression is that coefficient
+  /// times the input plus terms that do not depend on it.
+  fn linear(&self, n: usize) -> Option<(i128, Self)> {
+    let mut rest = self.clone();
+    let coefficient = rest.terms.remove(&vec![Atom::Input(n)])?.to_i64()?;
+    if rest.inputs().contains(&n) {
+      return None;
+    }
+    Some((coefficient.into(), rest))
+  }
+  /// The values the expression can take with each input within its bounds, ignoring overflow.
+  fn interval(&self, bounds: &[Interval]) -> Interval {
+    let mut sum = Interval::exactly(0);
+    for (atoms, w) in &self.terms {
+      let mut term = w
+        .to_i64()
+        .map_or(Interval::UNBOUNDED, |w| Interval::exactly(w.into()));
+      for atom in atoms {
+        term = term.mul(atom.interval(bounds));
+      }
+      sum = sum.add(term);
+    }
+    sum
+  }
+}
+
+impl<W: Word> Atom<W> {
+  fn interval(&self, bounds: &[Interval]) -> Interval {
+    let holds = |holds: bool| Interval::exactly(holds as i128);
+    match self {
+      Self::Input(n) => bounds.get(*n).copied().unwrap_or(Interval::UNBOUNDED),
+      Self::Negative(e) => match e.interval(bounds) {
+        i if i.hi < Bound::At(0) => holds(true),
+        i if i.lo >= Bound::At(0) => holds(false),
+        _ => Interval::BOOLEAN,
+      },
+      Self::Zero(e) => match e.interval(bounds) {
+        i if i == Interval::exactly(0) => holds(true),
+        i if !i.contains(0) => holds(false),
+        _ => Interval::BOOLEAN,
+      },
+    }
+  }
+}
+
+/// One end of an `Interval`, either a value or no bound at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Bound {
+  Below,
+  At(i128),
+  Above,
+}
+
+impl Bound {
+  fn signum(self) -> i128 {
+    match self {
+      Self::Below => -1,
+      Self::At(a) => a.signum(),
+      Self::Above => 1,
+    }
+  }
+  /// No bound in the direction of `signum`. Results too big for an `i128` end up here too,
+  /// which only makes an interval wider than it needs to be.
+  fn unbounded(signum: i128) -> Self {
+    if signum < 0 {
+      Self::Below
+    } else {
+      Self::Above
+    }
+  }
+  fn add(self, other: Self) -> Self {
+    match (self, other) {
+      (Self::At(a), Self::At(b)) => a
+        .checked_add(b)
+        .map_or(Self::unbounded(a.signum()), Self::At),
+      (Self::At(_), b) => b,
+      (a, _) => a,
+    }
+  }
+  fn mul(self, other: Self) -> Self {
+    match (self, other) {
+      (Self::At(a), Self::At(b)) => a
+        .checked_mul(b)
+        .map_or(Self::unbounded(a.signum() * b.signum()), Self::At),
+      (Self::At(0), _) | (_, Self::At(0)) => Self::At(0),
+      (a, b) => Self::unbounded(a.signum() * b.signum()),
+    }
+  }
+}
+
+/// The values an input or an expression can take, both ends included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Interval {
+  lo: Bound,
+  hi: Bound,
+}
+
+impl Interval {
+  const UNBOUNDED: Self = Self {
+    lo: Bound::Below,
+    hi: Bound::Above,
+  };
+  /// The values a comparison can take.
+  const BOOLEAN: Self = Self {
+    lo: Bound::At(0),
+    hi: Bound::At(1),
+  };
+  fn exactly(value: i128) -> Self {
+    Self {
+      lo: Bound::At(value),
+      hi: Bound::At(value),
+    }
+  }
+  fn add(self, other: Self) -> Self {
+    Self {
+      lo: self.lo.add(other.lo),
+      hi: self.hi.add(other.hi),
+    }
+  }
+  fn mul(self, other: Self) -> Self {
+    let corners = [
+      self.lo.mul(other.lo),
+      self.lo.mul(other.hi),
+      self.hi.mul(other.lo),
+      self.hi.mul(other.hi),
+    ];
+    Self {
+      lo: *corners.iter().min().unwrap(),
+      hi: *corners.iter().max().unwrap(),
+    }
+  }
+  fn intersect(self, other: Self) -> Self {
+    Self {
+      lo: self.lo.max(other.lo),
+      hi: self.hi.min(other.hi),
+    }
+  }
+  fn contains(&self, value: i128) -> bool {
+    self.lo <= Bound::At(value) && Bound::At(value) <= self.hi
+  }
+  fn is_empty(&self) -> bool {
+    self.lo > self.hi
+  }
+}
+
+impl<W: Word> Display for Atom<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::Input(n) => write!(f, "in{}", n),
+      Self::Negative(e) => {
+        let (l, r) = e.sides();
+        write!(f, "({} < {})", l, r)
+      }
+      Self::Zero(e) => {
+        let (l, r) = e.sides();
+        write!(f, "({} == {})", l, r)
+      }
+    }
+  }
+}
+
+impl<W: Word> Display for Expr<W> {
+  /// Terms in order of degree, highest first, with the constant last.
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    if self.terms.is_empty() {
+      return f.write_str("0");
+    }
+    let mut terms = self.terms.iter().collect::<Vec<_>>();
+    terms.sort_by_key(|(atoms, _)| std::cmp::Reverse(atoms.len()));
+    let one = W::from_i64(1);
+    for (n, (atoms, w)) in terms.into_iter().enumerate() {
+      let negative = *w < W::default();
+      let magnitude = if negative {
+        w.wrapping_mul(&W::from_i64(-1))
+      } else {
+        w.clone()
+      };
+      match (n, negative) {
+        (0, true) => f.write_str("-")?,
+        (0, false) => {}
+        (_, true) => f.write_str(" - ")?,
+        (_, false) => f.write_str(" + ")?,
+      }
+      let mut factors = atoms.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+      if magnitude != one || factors.is_empty() {
+        factors.insert(0, magnitude.to_string());
+      }
+      f.write_str(&factors.join("*"))?;
+    }
+    Ok(())
+  }
+}
+
+/// A jump condition a path depends on, holding when `expr` is non-zero if `nonzero` is set
+/// and when it is zero otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition<W = I> {
+  pub expr: Expr<W>,
+  pub nonzero: bool,
+}
+
+impl<W: Word> Condition<W> {
+  pub fn holds(&self, inputs: &[W]) -> bool {
+    self.expr.eval(inputs).is_zero() != self.nonzero
+  }
+  /// Expressions the condition says are below zero, ignoring overflow, so that conditions
+  /// that contradict each other can be spotted.
+  fn below_zero(&self) -> Vec<Expr<W>> {
+    let minus_one = || Expr::constant(W::from_i64(-1));
+    match (self.expr.comparison(), self.nonzero) {
+      (Some(Atom::Negative(d)), true) => vec![(**d).clone()],
+      (Some(Atom::Negative(d)), false) => vec![Expr::sum((**d).clone().negate(), minus_one())],
+      (Some(Atom::Zero(d)), true) => vec![
+        Expr::sum((**d).clone(), minus_one()),
+        Expr::sum((**d).clone().negate(), minus_one()),
+      ],
+      (None, false) => vec![
+        Expr::sum(self.expr.clone(), minus_one()),
+        Expr::sum(self.expr.clone().negate(), minus_one()),
+      ],
+      _ => vec![],
+    }
+  }
+  /// The expression the condition says is not zero, if that is what it says.
+  fn not_zero(&self) -> Option<&Expr<W>> {
+    match (self.expr.comparison(), self.nonzero) {
+      (Some(Atom::Zero(d)), false) => Some(d),
+      (None, true) => Some(&self.expr),
+      _ => None,
+    }
+  }
+  /// Whether the condition holds for all inputs within `bounds` or for none of them.
+  fn within(&self, bounds: &[Interval]) -> Option<bool> {
+    match self.expr.interval(bounds) {
+      i if i == Interval::exactly(0) => Some(!self.nonzero),
+      i if !i.contains(0) => Some(self.nonzero),
+      _ => None,
+    }
+  }
+  /// Conditions that hold exactly when input `n` is within `bounds`, along with the bounds
+  /// they state. Ends too big for an `i64` are left out.
+  fn bounding(n: usize, bounds: Interval) -> (Interval, Vec<Self>) {
+    let word = |v: i128| i64::try_from(v).ok().map(W::from_i64).map(Expr::constant);
+    let lo = match bounds.lo {
+      Bound::At(lo) => word(lo),
+      _ => None,
+    };
+    let hi = match bounds.hi {
+      Bound::At(hi) => hi.checked_add(1).and_then(word),
+      _ => None,
+    };
+    let stated = Interval {
+      lo: lo.as_ref().map_or(Bound::Below, |_| bounds.lo),
+      hi: hi.as_ref().map_or(Bound::Above, |_| bounds.hi),
+    };
+    let conditions = match (lo, hi) {
+      // Built by hand rather than with `equals`, so it reads `in == value` for any value.
+      (Some(lo), Some(_)) if bounds.lo == bounds.hi => vec![Self {
+        expr: Expr::term(
+          vec![Atom::Zero(Rc::new(Expr::sum(Expr::input(n), lo.negate())))],
+          W::from_i64(1),
+        ),
+        nonzero: true,
+      }],
+      (lo, hi) => {
+        let at_least = lo.map(|lo| Self {
+          expr: Expr::less_than(Expr::input(n), lo),
+          nonzero: false,
+        });
+        let below = hi.map(|hi| Self {
+          expr: Expr::less_than(Expr::input(n), hi),
+          nonzero: true,
+        });
+        at_least.into_iter().chain(below).collect()
+      }
+    };
+    (stated, conditions)
+  }
+}
+
+impl<W: Word> Display for Condition<W> {
+  /// Comparisons are written as the inequality that holds, such as `a >= b` for `a < b` being
+  /// false.
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    let comparison = self.expr.comparison().map(|atom| match atom {
+      Atom::Negative(e) => (e.sides(), if self.nonzero { "<" } else { ">=" }),
+      Atom::Zero(e) => (e.sides(), if self.nonzero { "==" } else { "!=" }),
+      Atom::Input(_) => unreachable!(),
+    });
+    match comparison {
+      Some(((l, r), op)) => write!(f, "{} {} {}", l, op, r),
+      None => write!(
+        f,
+        "{} {} 0",
+        self.expr,
+        if self.nonzero { "!=" } else { "==" }
+      ),
+    }
+  }
+}
+
+/// Why a path stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum End<W = I> {
+  Halted,
+  /// The program wanted more inputs than the executor was told to give it.
+  Input,
+  /// The path ran out of steps, usually in a loop on an input, or there were too many paths.
+  Limit,
+  /// The instruction at `address` depends on an input in a way that cannot be forked on: its
+  /// own words, an address it uses or a jump target.
+  Symbolic {
+    address: usize,
+  },
+  Error(IntcodeError<W>),
+}
+
+/// One way through a program: the conditions the inputs have to meet to take it and the
+/// outputs it produces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<W = I> {
+  pub conditions: Vec<Condition<W>>,
+  pub outputs: Vec<Expr<W>>,
+  pub end: End<W>,
+}
+
+impl<W: Word> Path<W> {
+  /// Whether the program takes this path on `inputs`.
+  pub fn holds(&self, inputs: &[W]) -> bool {
+    self.conditions.iter().all(|c| c.holds(inputs))
+  }
+}
+
+impl<W: Word> Display for Path<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    let conditions = self
+      .conditions
+      .iter()
+      .map(|c| c.to_string())
+      .collect::<Vec<_>>();
+    let outputs = self
+      .outputs
+      .iter()
+      .map(|o| o.to_string())
+      .collect::<Vec<_>>();
+    if !conditions.is_empty() {
+      write!(f, "if {}: ", conditions.join(" && "))?;
+    }
+    write!(f, "outputs [{}], ", outputs.join(", "))?;
+    match &self.end {
+      End::Halted => write!(f, "halts"),
+      End::Input => write!(f, "wants more input"),
+      End::Limit => write!(f, "gave up"),
+      End::Symbolic { address } => write!(f, "depends on input at {}", address),
+      End::Error(e) => e.fmt(f),
+    }
+  }
+}
+
+/// Runs a program with its inputs as symbols, forking wherever a comparison or jump depends on
+/// them, to find every path through it along with the conditions that lead there.
+///
+/// Each path keeps bounds on every input, narrowed by its conditions, and is dropped once no
+/// inputs are left that could take it. A finished path states its conditions on a single
+/// input as those bounds, without the conditions the bounds already imply. Like the rest of
+/// the executor, the bounds ignore overflow.
+#[derive(Clone, Debug)]
+pub struct Symbolic<'a, W = I> {
+  codes: &'a [W],
+  inputs: usize,
+  steps: usize,
+  paths: usize,
+}
+
+#[derive(Clone)]
+struct State<W> {
+  position: usize,
+  relative_position: W,
+  /// Every word written so far. The rest still hold the image.
+  memory: BTreeMap<usize, Expr<W>>,
+  inputs: usize,
+  steps: usize,
+  /// What the path's conditions allow each input to be.
+  bounds: Vec<Interval>,
+  path: Path<W>,
+}
+
+impl<'a, W: Word> Symbolic<'a, W> {
+  /// Explores `codes` giving it up to `inputs` symbolic inputs.
+  pub fn new(codes: &'a [W], inputs: usize) -> Self {
+    Self {
+      codes,
+      inputs,
+      steps: 10_000,
+      paths: 1_000,
+    }
+  }
+  /// The most instructions to follow along any one path.
+  pub fn steps(mut self, steps: usize) -> Self {
+    self.steps = steps;
+    self
+  }
+  /// The most paths to find.
+  pub fn paths(mut self, paths: usize) -> Self {
+    self.paths = paths;
+    self
+  }
+  /// Every path through the program, in the order they were finished.
+  pub fn run(&self) -> Vec<Path<W>> {
+    let start = State {
+      position: 0,
+      relative_position: W::default(),
+      memory: BTreeMap::new(),
+      inputs: 0,
+      steps: 0,
+      bounds: vec![Interval::UNBOUNDED; self.inputs],
+      path: Path {
+        conditions: vec![],
+        outputs: vec![],
+        end: End::Halted,
+      },
+    };
+    let mut pending = vec![start];
+    let mut paths = vec![];
+    'paths: while let Some(mut state) = pending.pop() {
+      let end = loop {
+        if state.steps == self.steps {
+          break End::Limit;
+        }
+        match state.step(self.codes, self.inputs) {
+          Ok(None) => state.steps += 1,
+          Ok(Some(_)) if paths.len() + pending.len() + 1 >= self.paths => break End::Limit,
+          Ok(Some(condition)) => {
+            let negation = Condition {
+              nonzero: !condition.nonzero,
+              ..condition.clone()
+            };
+            let mut holds = state.clone();
+            if holds.assume(condition) {
+              pending.push(holds);
+            }
+            if !state.assume(negation) {
+              continue 'paths;
+            }
+          }
+          Err(end) => break end,
+        }
+      };
+      state.path.end = end;
+      state.simplify();
+      paths.push(state.path);
+    }
+    paths
+  }
+}
+
+impl<W: Word> State<W> {
+  fn word(&self, codes: &[W], address: usize) -> Expr<W> {
+    match self.memory.get(&address) {
+      Some(e) => e.clone(),
+      None => Expr::constant(codes.get(address).cloned().unwrap_or_default()),
+    }
+  }
+  fn to_address(at: usize, target: W) -> Result<usize, End<W>> {
+    match target.to_i64() {
+      Some(a) if a >= 0 => Ok(a as usize),
+      _ => Err(End::Error(IntcodeError::NegativeAddress {
+        address: at,
+        target,
+      })),
+    }
+  }
+  fn read(&self, codes: &[W], at: usize, p: &ParameterMode<W>) -> Result<Expr<W>, End<W>> {
+    match p.address(&self.relative_position) {
+      Some(address) => Ok(self.word(codes, Self::to_address(at, address)?)),
+      None => Ok(Expr::constant(p.word())),
+    }
+  }
+  fn write(&mut self, at: usize, p: &ParameterMode<W>, value: Expr<W>) -> Result<(), End<W>> {
+    match p.address(&self.relative_position) {
+      Some(address) => {
+        self.memory.insert(Self::to_address(at, address)?, value);
+        Ok(())
+      }
+      None => Err(End::Error(IntcodeError::WriteToImmediate { address: at })),
+    }
+  }
+  /// Whether `condition` has to hold, or cannot, given the conditions the path already
+  /// depends on.
+  fn decide(&self, condition: &Condition<W>) -> Option<bool> {
+    if let Some(known) = self
+      .path
+      .conditions
+      .iter()
+      .find(|c| c.expr == condition.expr)
+    {
+      return Some(known.nonzero == condition.nonzero);
+    }
+    if let Some(holds) = condition.within(&self.bounds) {
+      return Some(holds);
+    }
+    let known = self
+      .path
+      .conditions
+      .iter()
+      .flat_map(Condition::below_zero)
+      .collect::<Vec<_>>();
+    // Two values below zero add up to -2 or less.
+    let contradicts = |c: &Condition<W>| {
+      c.below_zero().into_iter().any(|a| {
+        known.iter().any(|b| {
+          let sum = Expr::sum(a.clone(), b.clone()).as_constant();
+          matches!(sum, Some(sum) if sum >= W::from_i64(-1))
+        })
+      })
+    };
+    let negation = Condition {
+      expr: condition.expr.clone(),
+      nonzero: !condition.nonzero,
+    };
+    if contradicts(condition) {
+      Some(false)
+    } else if contradicts(&negation) {
+      Some(true)
+    } else {
+      None
+    }
+  }
+  /// Adds `condition` to the path and narrows the inputs' bounds to match. Returns false if no
+  /// inputs are left that could take the path.
+  fn assume(&mut self, condition: Condition<W>) -> bool {
+    self.path.conditions.push(condition);
+    self.narrow()
+  }
+  /// Narrows each input's bounds by the conditions that are linear in it, taking the other
+  /// inputs at their bounds, until they stop changing.
+  fn narrow(&mut self) -> bool {
+    let conditions = &self.path.conditions;
+    let below_zero = conditions
+      .iter()
+      .flat_map(Condition::below_zero)
+      .collect::<Vec<_>>();
+    let not_zero = conditions
+      .iter()
+      .filter_map(Condition::not_zero)
+      .cloned()
+      .collect::<Vec<_>>();
+    for _ in 0..NARROWING_ROUNDS {
+      let before = self.bounds.clone();
+      for e in &below_zero {
+        for n in e.inputs() {
+          // c*in + rest <= -1, so c*in <= -1 - rest at its smallest.
+          let (c, rest) = match e.linear(n) {
+            Some(linear) => linear,
+            None => continue,
+          };
+          let k = match rest.interval(&self.bounds).lo {
+            Bound::At(lo) => match (-1i128).checked_sub(lo) {
+              Some(k) => k,
+              None => continue,
+            },
+            _ => continue,
+          };
+          let bound = if c > 0 {
+            Interval {
+              lo: Bound::Below,
+              hi: Bound::At(k.div_euclid(c)),
+            }
+          } else {
+            Interval {
+              lo: Bound::At(-(k.div_euclid(-c))),
+              hi: Bound::Above,
+            }
+          };
+          self.bounds[n] = self.bounds[n].intersect(bound);
+        }
+      }
+      // A value that cannot be zero moves a bound that sits on the root.
+      for e in &not_zero {
+        for n in e.inputs() {
+          let root = e.linear(n).and_then(|(c, rest)| {
+            let k = i128::from(rest.as_constant()?.to_i64()?);
+            Some(-k / c).filter(|_| k % c == 0)
+          });
+          if let Some(root) = root {
+            let bounds = &mut self.bounds[n];
+            if bounds.lo == Bound::At(root) {
+              bounds.lo = Bound::At(root + 1);
+            }
+            if bounds.hi == Bound::At(root) {
+              bounds.hi = Bound::At(root - 1);
+            }
+          }
+        }
+      }
+      let bounds = &self.bounds;
+      if bounds.iter().any(Interval::is_empty)
+        || conditions.iter().any(|c| c.within(bounds) == Some(false))
+      {
+        return false;
+      }
+      if *bounds == before {
+        break;
+      }
+    }
+    true
+  }
+  /// Drops conditions that repeat or that follow from the bounds on the inputs, and writes the
+  /// bounds of every input some condition depends on alone in front of the rest.
+  fn simplify(&mut self) {
+    let mut conditions = vec![];
+    for condition in std::mem::take(&mut self.path.conditions) {
+      if !conditions.contains(&condition) {
+        conditions.push(condition);
+      }
+    }
+    let single = conditions
+      .iter()
+      .map(|c| c.expr.inputs())
+      .filter(|inputs| inputs.len() == 1)
+      .flatten()
+      .collect::<BTreeSet<_>>();
+    let mut bounding = vec![];
+    let mut bounds = vec![Interval::UNBOUNDED; self.bounds.len()];
+    // Only the bounds that are written down may be used to drop other conditions.
+    for n in single {
+      let (stated, conditions) = Condition::bounding(n, self.bounds[n]);
+      bounds[n] = stated;
+      bounding.extend(conditions);
+    }
+    conditions.retain(|c| c.within(&bounds) != Some(true));
+    bounding.extend(conditions);
+    self.path.conditions = bounding;
+  }
+  /// Whether a condition holds, if it does not depend on the inputs or is settled by the
+  /// conditions the path already depends on.
+  fn known(&self, condition: &Condition<W>) -> Option<bool> {
+    match condition.expr.as_constant() {
+      Some(c) => Some(c.is_zero() != condition.nonzero),
+      None => self.decide(condition),
+    }
+  }
+  /// Executes one instruction. Returns without changing anything if a comparison or a jump
+  /// could go either way, with the condition to fork on; once one side of it is on the path,
+  /// stepping again carries on down that side.
+  fn step(&mut self, codes: &[W], inputs: usize) -> Result<Option<Condition<W>>, End<W>> {
+    let at = self.position;
+    let symbolic = End::Symbolic { address: at };
+    let concrete = |address| self.word(codes, address).as_constant();
+    let opcode =
+      OpCode::decode_with(|a| concrete(a).unwrap_or_default(), at).map_err(End::Error)?;
+    if (at..at + opcode.size()).any(|a| concrete(a).is_none()) {
+      return Err(symbolic);
+    }
+    let next = at + opcode.size();
+    match &opcode {
+      OpCode::Add(p1, p2, p3) | OpCode::Multiply(p1, p2, p3) => {
+        let (a, b) = (self.read(codes, at, p1)?, self.read(codes, at, p2)?);
+        let value = match opcode {
+          OpCode::Add(_, _, _) => Expr::sum(a, b),
+          _ => Expr::product(a, b),
+        };
+        self.write(at, p3, value)?;
+      }
+      // Comparisons fork rather than store their result, so that values stay sums of
+      // products of inputs and every condition is an inequality between them.
+      OpCode::LessThan(p1, p2, p3) | OpCode::Equals(p1, p2, p3) => {
+        let (a, b) = (self.read(codes, at, p1)?, self.read(codes, at, p2)?);
+        let condition = Condition {
+          expr: match opcode {
+            OpCode::LessThan(_, _, _) => Expr::less_than(a, b),
+            _ => Expr::equals(a, b),
+          },
+          nonzero: true,
+        };
+        match self.known(&condition) {
+          Some(holds) => self.write(at, p3, Expr::constant(W::from_i64(holds as i64)))?,
+          None => return Ok(Some(condition)),
+        }
+      }
+      OpCode::Input(_) if self.inputs == inputs => return Err(End::Input),
+      OpCode::Input(p1) => {
+        self.write(at, p1, Expr::input(self.inputs))?;
+        self.inputs += 1;
+      }
+      OpCode::Output(p1) => {
+        let output = self.read(codes, at, p1)?;
+        self.path.outputs.push(output);
+      }
+      OpCode::AdjustRelativeBase(p1) => match self.read(codes, at, p1)?.as_constant() {
+        Some(w) => self.relative_position = self.relative_position.wrapping_add(&w),
+        None => return Err(symbolic),
+      },
+      OpCode::JumpIfTrue(p1, p2) | OpCode::JumpIfFalse(p1, p2) => {
+        let condition = Condition {
+          expr: self.read(codes, at, p1)?,
+          nonzero: matches!(opcode, OpCode::JumpIfTrue(_, _)),
+        };
+        let taken = match self.known(&condition) {
+          Some(taken) => taken,
+          None => return Ok(Some(condition)),
+        };
+        self.position = match (taken, self.read(codes, at, p2)?.as_constant()) {
+          (false, _) => next,
+          (true, Some(target)) => Self::to_address(at, target)?,
+          (true, None) => return Err(symbolic),
+        };
+        return Ok(None);
+      }
+      OpCode::Break => return Err(End::Halted),
+      OpCode::Custom { .. } => unreachable!("only standard opcodes are decoded"),
+    }
+    self.position = next;
+    Ok(None)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::intcode::assemble;
+
+  #[test]
+  fn test_symbolic() {
+    // Outputs 1 if 3 * in0 < in1 and 0 otherwise.
+    let codes: Vec<I> = vec![
+      3, 30, 3, 31, 1002, 30, 3, 32, 7, 32, 31, 33, 1005, 33, 18, 104, 0, 99, 104, 1, 99,
+    ];
+    let paths = Symbolic::new(&codes, 2).run();
+    let lines = paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+      lines,
+      [
+        "if 3*in0 >= in1: outputs [0], halts",
+        "if 3*in0 < in1: outputs [1], halts",
+      ]
+    );
+    for (x, y) in &[(1, 2), (1, 3), (1, 4), (-5, 0)] {
+      let path = paths.iter().find(|p| p.holds(&[*x, *y])).unwrap();
+      assert_eq!(path.outputs[0].eval(&[*x, *y]), (3 * x < *y) as I);
+    }
+
+    assert_eq!(Symbolic::new(&codes, 1).run()[0].end, End::Input);
+    // A loop counting down from the input never ends on its own.
+    let countdown: Vec<I> = vec![3, 9, 1001, 9, -1, 9, 1005, 9, 2, 0];
+    let paths = Symbolic::new(&countdown, 1).steps(100).paths(5).run();
+    assert_eq!(paths.len(), 5);
+    assert!(paths.iter().any(|p| p.end == End::Limit));
+  }
+
+  #[test]
+  fn test_pruning() {
+    // Shaped like the day 19 drone: 1 inside a beam between y = x / 2 and y = 2 * x, checking
+    // the same inputs again in different forms along the way.
+    let codes = assemble(
+      "
+            in [x]
+            in [y]
+            lt [x], #0, [t]
+            jt [t], #outside
+            lt [y], #0, [t]
+            jt [t], #outside
+            mul [x], #2, [a]
+            mul [y], #2, [b]
+            eq [x], #0, [t]
+            jf [t], #beam
+            lt #0, [b], [t]
+            jt [t], #outside
+            out #1
+            hlt
+      beam: lt [a], [y], [t]
+            jt [t], #outside
+            lt [b], [x], [t]
+            jt [t], #outside
+            lt #0, [a], [t]
+            jf [t], #outside
+            out #1
+            hlt
+      outside: out #0
+            hlt
+      x: .data 0
+      y: .data 0
+      t: .data 0
+      a: .data 0
+      b: .data 0
+      ",
+    )
+    .unwrap();
+    let paths = Symbolic::new(&codes, 2).run();
+    let lines = paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+      lines,
+      [
+        "if in0 >= 1 && in1 >= 1 && 2*in0 >= in1 && 2*in1 >= in0: outputs [1], halts",
+        "if in0 >= 1 && in1 >= 0 && 2*in0 >= in1 && 2*in1 < in0: outputs [0], halts",
+        "if in0 >= 1 && in1 >= 3 && 2*in0 < in1: outputs [0], halts",
+        "if in0 == 0 && in1 == 0: outputs [1], halts",
+        "if in0 == 0 && in1 >= 1: outputs [0], halts",
+        "if in0 >= 0 && in1 < 0: outputs [0], halts",
+        "if in0 < 0: outputs [0], halts",
+      ]
+    );
+    // Every path is taken by some inputs, and by exactly the ones that should take it.
+    for path in &paths {
+      assert!((-2..8).any(|x| (-2..8).any(|y| path.holds(&[x, y]))));
+    }
+    for x in -2..8 {
+      for y in -2..8 {
+        let taken = paths
+          .iter()
+          .filter(|p| p.holds(&[x, y]))
+          .collect::<Vec<_>>();
+        assert_eq!(taken.len(), 1);
+        let inside = x >= 0 && y >= 0 && y <= 2 * x && x <= 2 * y;
+        assert_eq!(taken[0].outputs[0].eval(&[x, y]), inside as I);
+      }
+    }
+  }
+}
@@ -0,0 +1,332 @@
+use super::{Event, IntcodeError, Program, System, Word, I};
+use std::fmt::{Display, Error, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+const HEADER: &str = "intcode-transcript 1";
+
+/// One value that crossed between a program and its system.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Entry<W = I> {
+  Input(W),
+  Output(W),
+}
+
+/// Every input a program consumed and every output it produced, in order.
+///
+/// Written as a header line followed by one `in <value>` or `out <value>` line per entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transcript<W = I> {
+  pub entries: Vec<Entry<W>>,
+}
+
+/// Wraps a `System`, recording everything that passes through it.
+#[derive(Clone, Debug, Default)]
+pub struct Recorder<S: System> {
+  pub system: S,
+  pub transcript: Transcript<S::Word>,
+}
+
+impl<S: System> System for Recorder<S> {
+  type Word = S::Word;
+
+  fn send_input(&mut self) -> Event<S::Word> {
+    let event = self.system.send_input();
+    if let Event::Input(i) | Event::BreakOnInput(i) = &event {
+      self.transcript.entries.push(Entry::Input(i.clone()));
+    }
+    event
+  }
+
+  fn take_output(&mut self, output: S::Word) -> Event<S::Word> {
+    self.transcript.entries.push(Entry::Output(output.clone()));
+    self.system.take_output(output)
+  }
+
+  fn end(&mut self) -> Option<S::Word> {
+    self.system.end()
+  }
+
+  fn reset(&mut self) {
+    self.transcript.entries.clear();
+    self.system.reset();
+  }
+
+  fn save_state(&self) -> Vec<S::Word> {
+    self.system.save_state()
+  }
+
+  fn restore_state(&mut self, state: &[S::Word]) {
+    self.system.restore_state(state);
+  }
+
+  fn unsend_input(&mut self, input: S::Word) {
+    if self.transcript.entries.last() == Some(&Entry::Input(input.clone())) {
+      self.transcript.entries.pop();
+    }
+    self.system.unsend_input(input);
+  }
+
+  fn untake_output(&mut self, output: &S::Word) {
+    if self.transcript.entries.last() == Some(&Entry::Output(output.clone())) {
+      self.transcript.entries.pop();
+    }
+    self.system.untake_output(output);
+  }
+}
+
+impl<S: System> Program<S> {
+  /// The same program with everything its system sends and receives recorded.
+  pub fn recorded(self) -> Program<Recorder<S>> {
+    self.map_system(|system| Recorder {
+      system,
+      transcript: Transcript::default(),
+    })
+  }
+}
+
+/// Where a replayed program stopped following its transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError<W = I> {
+  /// The program asked for input where entry `index` was expected.
+  UnexpectedInput {
+    index: usize,
+    expected: Entry<W>,
+  },
+  /// The program wrote `output` where entry `index`, if any, was expected.
+  UnexpectedOutput {
+    index: usize,
+    expected: Option<Entry<W>>,
+    output: W,
+  },
+  /// The program halted before reaching entry `index`.
+  Unfinished {
+    index: usize,
+    expected: Entry<W>,
+  },
+  Intcode(IntcodeError<W>),
+}
+
+impl<W: Word> Display for ReplayError<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::UnexpectedInput { index, expected } => write!(
+        f,
+        "entry {}: expected '{}', the program asked for input",
+        index, expected
+      ),
+      Self::UnexpectedOutput {
+        index,
+        expected: Some(expected),
+        output,
+      } => write!(
+        f,
+        "entry {}: expected '{}', the program wrote {}",
+        index, expected, output
+      ),
+      Self::UnexpectedOutput {
+        index,
+        expected: None,
+        output,
+      } => write!(
+        f,
+        "entry {}: the transcript ended, the program wrote {}",
+        index, output
+      ),
+      Self::Unfinished { index, expected } => write!(
+        f,
+        "entry {}: expected '{}', the program halted",
+        index, expected
+      ),
+      Self::Intcode(e) => write!(f, "{}", e),
+    }
+  }
+}
+
+impl<W: Word> std::error::Error for ReplayError<W> {}
+
+/// Feeds a transcript's inputs to a program and stops it at the first output that differs.
+struct Replay<'a, W> {
+  entries: &'a [Entry<W>],
+  next: usize,
+  error: Option<ReplayError<W>>,
+}
+
+impl<'a, W: Word> System for Replay<'a, W> {
+  type Word = W;
+
+  fn send_input(&mut self) -> Event<W> {
+    match self.entries.get(self.next) {
+      Some(Entry::Input(i)) => {
+        self.next += 1;
+        Event::Input(i.clone())
+      }
+      Some(expected) => {
+        self.error = Some(ReplayError::UnexpectedInput {
+          index: self.next,
+          expected: expected.clone(),
+        });
+        Event::AskForInputAgain
+      }
+      // A recorded session ends with the program waiting for more input.
+      None => Event::AskForInputAgain,
+    }
+  }
+
+  fn take_output(&mut self, output: W) -> Event<W> {
+    match self.entries.get(self.next) {
+      Some(Entry::Output(o)) if *o == output => {
+        self.next += 1;
+        Event::Output(output)
+      }
+      expected => {
+        self.error = Some(ReplayError::UnexpectedOutput {
+          index: self.next,
+          expected: expected.cloned(),
+          output,
+        });
+        Event::Halted
+      }
+    }
+  }
+}
+
+impl<W: Word> Transcript<W> {
+  /// Runs `program` on the recorded inputs and checks it produces exactly the recorded outputs.
+  pub fn replay<S: System<Word = W>>(&self, program: Program<S>) -> Result<(), ReplayError<W>> {
+    let mut program = program.map_system(|_| Replay {
+      entries: &self.entries,
+      next: 0,
+      error: None,
+    });
+    let result = program.run();
+    if let Some(error) = program.system.error.take() {
+      return Err(error);
+    }
+    result.map_err(ReplayError::Intcode)?;
+    match self.entries.get(program.system.next) {
+      Some(expected) => Err(ReplayError::Unfinished {
+        index: program.system.next,
+        expected: expected.clone(),
+      }),
+      None => Ok(()),
+    }
+  }
+  pub fn inputs(&self) -> impl Iterator<Item = &W> {
+    self.entries.iter().filter_map(|e| match e {
+      Entry::Input(i) => Some(i),
+      Entry::Output(_) => None,
+    })
+  }
+  pub fn outputs(&self) -> impl Iterator<Item = &W> {
+    self.entries.iter().filter_map(|e| match e {
+      Entry::Output(o) => Some(o),
+      Entry::Input(_) => None,
+    })
+  }
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, self.to_string())
+  }
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+    fs::read_to_string(path)?
+      .parse()
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+impl<W: Word> Display for Entry<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    match self {
+      Self::Input(i) => write!(f, "in {}", i),
+      Self::Output(o) => write!(f, "out {}", o),
+    }
+  }
+}
+
+impl<W: Word> Display for Transcript<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+    writeln!(f, "{}", HEADER)?;
+    for entry in &self.entries {
+      writeln!(f, "{}", entry)?;
+    }
+    Ok(())
+  }
+}
+
+impl<W: Word> FromStr for Transcript<W> {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut lines = s.lines();
+    if lines.next() != Some(HEADER) {
+      return Err("not an intcode transcript".into());
+    }
+    let number = |s: &str| {
+      s.trim()
+        .parse::<W>()
+        .map_err(|_| format!("invalid number '{}'", s))
+    };
+    let mut transcript = Self::default();
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+      let entry = match line.trim().split_once(' ') {
+        Some(("in", i)) => Entry::Input(number(i)?),
+        Some(("out", o)) => Entry::Output(number(o)?),
+        _ => return Err(format!("invalid line '{}'", line)),
+      };
+      transcript.entries.push(entry);
+    }
+    Ok(transcript)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_transcript() {
+    // Doubles every input until it reads a zero.
+    let codes = "3,15,1006,15,14,1002,15,2,15,4,15,1105,1,0,99";
+    let mut program = codes.parse::<Program>().unwrap().recorded();
+    program.system.system.input.extend(vec![3, 5, 0]);
+    program.run().unwrap();
+    let transcript = program.system.transcript;
+    assert_eq!(
+      transcript.to_string(),
+      "intcode-transcript 1\nin 3\nout 6\nin 5\nout 10\nin 0\n"
+    );
+    assert_eq!(transcript.to_string().parse(), Ok(transcript.clone()));
+    assert_eq!(transcript.inputs().collect::<Vec<_>>(), [&3, &5, &0]);
+    assert_eq!(transcript.outputs().collect::<Vec<_>>(), [&6, &10]);
+
+    let replay = |codes: &str| transcript.replay(codes.parse::<Program>().unwrap());
+    assert_eq!(replay(codes), Ok(()));
+    // Triples instead.
+    assert_eq!(
+      replay("3,15,1006,15,14,1002,15,3,15,4,15,1105,1,0,99"),
+      Err(ReplayError::UnexpectedOutput {
+        index: 1,
+        expected: Some(Entry::Output(6)),
+        output: 9
+      })
+    );
+    // Halts after the first output.
+    assert_eq!(
+      replay("3,15,1006,15,14,1002,15,2,15,4,15,99,1,0,99"),
+      Err(ReplayError::Unfinished {
+        index: 2,
+        expected: Entry::Input(5)
+      })
+    );
+    // Reads twice before writing.
+    assert_eq!(
+      replay("3,15,3,15,99"),
+      Err(ReplayError::UnexpectedInput {
+        index: 1,
+        expected: Entry::Output(6)
+      })
+    );
+  }
+}
@@ -0,0 +1,550 @@
+use super::{Block, Cfg, Exit, OpCode, ParameterMode, I};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// The start of every translation.
+const HEADER: &str = "//! Translated from Intcode by `intcode aot`. Do not edit.
+#![allow(dead_code, clippy::all)]
+use adventofcode2019::intcode::{Event, IntcodeError, Program, System, I};
+
+/// Runs a `Program` through the translated blocks, handing any address that was not
+/// translated to the interpreter one instruction at a time.
+///
+/// Blocks whose code has been overwritten are interpreted from then on, apart from operands
+/// the program itself patches, which are read from memory. Everything is interpreted when
+/// checked arithmetic, limits, profiling or coverage are on, since the translation implements
+/// none of them.
+pub struct Compiled<S: System<Word = I>> {
+  pub program: Program<S>,
+  interpreted: bool,
+  stale: Vec<bool>,
+}
+
+impl<S: System<Word = I>> Compiled<S> {
+  pub fn new(program: Program<S>) -> Self {
+    let mut compiled = Self {
+      program,
+      interpreted: false,
+      stale: vec![],
+    };
+    compiled.check();
+    compiled
+  }
+  pub fn reset(&mut self) {
+    self.program.reset();
+    self.check();
+  }
+  /// Same as `Program::run`.
+  pub fn run(&mut self) -> Result<Option<I>, IntcodeError> {
+    loop {
+      match self.run_to_event()? {
+        Event::Halted | Event::AskForInputAgain => break,
+        _ => {}
+      }
+    }
+    let output = self.program.system.end();
+    if self.program.reset_after_run {
+      self.reset();
+    }
+    Ok(output)
+  }
+  /// Same as `Program::run_to_event`.
+  pub fn run_to_event(&mut self) -> Result<Event, IntcodeError> {
+    loop {
+      let event = if self.interpreted {
+        self.interpret()
+      } else {
+        self.block().map_err(|e| {
+          self.program.position = e.address();
+          e
+        })
+      };
+      match event? {
+        Some(Event::Input(_)) | Some(Event::Output(_)) | None => {}
+        Some(e) => return Ok(e),
+      }
+    }
+  }
+  fn check(&mut self) {
+    let program = &self.program;
+    self.interpreted = program.checked
+      || program.limits.is_some()
+      || program.profile.is_some()
+      || program.coverage.is_some();
+    self.stale = BLOCKS
+      .iter()
+      .map(|&(start, end)| (start..end).any(|a| is_code(a) && program.codes.get(a) != IMAGE[a]))
+      .collect();
+  }
+  /// Executes one instruction in the interpreter, noticing if it overwrites code.
+  fn interpret(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let written = self
+      .program
+      .opcode()
+      .ok()
+      .and_then(|opcode| opcode.destination())
+      .and_then(|p| self.program.destination(&p).ok())
+      .filter(|address| is_code(*address));
+    let old = written.map(|address| self.program.codes.get(address));
+    let event = self.program.step()?;
+    if let (Some(address), Some(old)) = (written, old) {
+      if self.program.codes.get(address) != old {
+        self.invalidate(address);
+      }
+    }
+    Ok(event)
+  }
+  fn invalidate(&mut self, address: usize) {
+    for (n, &(start, end)) in BLOCKS.iter().enumerate() {
+      if start <= address && address < end {
+        self.stale[n] = true;
+      }
+    }
+  }
+  fn relative(&self, at: usize, offset: I) -> Result<usize, IntcodeError> {
+    self.address(at, self.program.relative_position.wrapping_add(offset))
+  }
+  fn address(&self, at: usize, target: I) -> Result<usize, IntcodeError> {
+    if target < 0 {
+      return Err(IntcodeError::NegativeAddress {
+        address: at,
+        target,
+      });
+    }
+    Ok(target as usize)
+  }
+  /// Writes to an address the translation knows is not code.
+  fn set(&mut self, address: usize, value: I) {
+    self.program.codes.set(address, value);
+    if let Some(cache) = &mut self.program.cache {
+      cache.invalidate(address);
+    }
+  }
+  /// Writes anywhere, returning whether translated code changed.
+  fn store(&mut self, address: usize, value: I) -> bool {
+    let changed = is_code(address) && self.program.codes.get(address) != value;
+    if changed {
+      self.invalidate(address);
+    }
+    self.set(address, value);
+    changed
+  }
+  fn input(&mut self, at: usize, destination: usize) -> Result<(Event, bool), IntcodeError> {
+    let event = self.program.system.send_input();
+    let changed = match event {
+      Event::Input(i) | Event::BreakOnInput(i) => self.store(destination, i),
+      Event::EndOfInput => return Err(IntcodeError::InputExhausted { address: at }),
+      _ => false,
+    };
+    Ok((event, changed))
+  }
+  /// Where to leave the program after the system answered an IO instruction with `event`, or
+  /// `None` to carry on with the block.
+  fn stop(&mut self, at: usize, next: usize, event: Event, changed: bool) -> Option<Option<Event>> {
+    self.program.position = match event {
+      Event::Input(_) | Event::Output(_) if !changed => return None,
+      Event::AskForInputAgain | Event::Halted => at,
+      _ => next,
+    };
+    Some(Some(event))
+  }
+}
+
+fn is_code(address: usize) -> bool {
+  CODE
+    .binary_search_by(|&(start, end)| {
+      if end <= address {
+        std::cmp::Ordering::Less
+      } else if start > address {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    })
+    .is_ok()
+}
+";
+
+/// Translates the code reachable in `codes` from address 0 and from `entries` to a Rust module
+/// defining `Compiled`, a drop-in replacement for `Program::run` and `Program::run_to_event` on
+/// programs loaded from the same image. The module uses the library through the
+/// `adventofcode2019` crate.
+///
+/// Only code that is actually run from an entry decides what happens, so anything can be
+/// given as an entry, such as the jump targets seen while tracing a run.
+pub fn translate(codes: &[I], entries: &[usize]) -> String {
+  let entries = std::iter::once(0)
+    .chain(entries.iter().copied())
+    .collect::<Vec<_>>();
+  let cfg = Cfg::with_entries(codes, &entries);
+  let blocks = cfg
+    .blocks
+    .values()
+    .filter(|b| b.start < b.end)
+    .copied()
+    .collect::<Vec<_>>();
+  let translator = Translator::new(codes, &blocks);
+  let pairs = |ranges: &mut dyn Iterator<Item = (usize, usize)>| {
+    ranges
+      .map(|(start, end)| format!("({}, {})", start, end))
+      .collect::<Vec<_>>()
+      .join(", ")
+  };
+
+  let mut rust = HEADER.to_string();
+  writeln!(rust, "\n/// The image the translation was made from.").unwrap();
+  writeln!(rust, "const IMAGE: &[I] = &[{}];", list(codes)).unwrap();
+  writeln!(
+    rust,
+    "/// Words built into the translation, as `(start, end)`."
+  )
+  .unwrap();
+  let mut ranges = translator.code.iter().copied();
+  writeln!(
+    rust,
+    "const CODE: &[(usize, usize)] = &[{}];",
+    pairs(&mut ranges)
+  )
+  .unwrap();
+  writeln!(rust, "/// Each translated block, as `(start, end)`.").unwrap();
+  let mut ranges = blocks.iter().map(|b| (b.start, b.end));
+  writeln!(
+    rust,
+    "const BLOCKS: &[(usize, usize)] = &[{}];",
+    pairs(&mut ranges)
+  )
+  .unwrap();
+  rust.push_str(BLOCK_START);
+  for (n, block) in blocks.iter().enumerate() {
+    writeln!(rust, "        {} if !self.stale[{}] => {{", block.start, n).unwrap();
+    for line in translator.block(block) {
+      writeln!(rust, "          {}", line).unwrap();
+    }
+    writeln!(rust, "        }}").unwrap();
+  }
+  rust.push_str(BLOCK_END);
+  rust
+}
+
+const BLOCK_START: &str = "
+impl<S: System<Word = I>> Compiled<S> {
+  /// Runs translated blocks from the current position until something stops them.
+  fn block(&mut self) -> Result<Option<Event>, IntcodeError> {
+    let mut pc = self.program.position;
+    loop {
+      pc = match pc {
+";
+
+const BLOCK_END: &str = "        _ => {
+          self.program.position = pc;
+          return self.interpret();
+        }
+      };
+    }
+  }
+}
+";
+
+fn list<T: ToString>(items: &[T]) -> String {
+  items
+    .iter()
+    .map(|i| i.to_string())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Where an instruction writes.
+enum Destination {
+  /// An address known when translating.
+  Fixed(usize),
+  /// An expression evaluating to the address.
+  Dynamic(String),
+}
+
+struct Translator<'a> {
+  codes: &'a [I],
+  /// Operands that instructions write to, such as jump targets set before a call. They are
+  /// read at run time instead of being built into the translation.
+  patched: BTreeSet<usize>,
+  /// Words built into the translation, as sorted `(start, end)` ranges.
+  code: Vec<(usize, usize)>,
+}
+
+impl<'a> Translator<'a> {
+  fn new(codes: &'a [I], blocks: &[Block]) -> Self {
+    let instructions = blocks
+      .iter()
+      .flat_map(|b| b.lines(codes))
+      .filter_map(|line| Some((line.address, line.opcode?)))
+      .collect::<Vec<_>>();
+    let written = instructions
+      .iter()
+      .filter_map(|(_, opcode)| match opcode.destination()? {
+        ParameterMode::Position(i) if i >= 0 => Some(i as usize),
+        _ => None,
+      })
+      .collect::<BTreeSet<_>>();
+    let patched = instructions
+      .iter()
+      .flat_map(|(address, opcode)| address + 1..address + opcode.size())
+      .filter(|address| written.contains(address))
+      .collect::<BTreeSet<_>>();
+    let words = blocks
+      .iter()
+      .flat_map(|b| b.start..b.end)
+      .filter(|address| !patched.contains(address))
+      .collect::<BTreeSet<_>>();
+    let mut code: Vec<(usize, usize)> = vec![];
+    for address in words {
+      match code.last_mut() {
+        Some((_, end)) if *end == address => *end += 1,
+        _ => code.push((address, address + 1)),
+      }
+    }
+    Self {
+      codes,
+      patched,
+      code,
+    }
+  }
+
+  fn is_code(&self, address: usize) -> bool {
+    self
+      .code
+      .iter()
+      .any(|&(start, end)| start <= address && address < end)
+  }
+
+  /// The body of a block's match arm, evaluating to the next position.
+  fn block(&self, block: &Block) -> Vec<String> {
+    let mut lines = vec![];
+    let mut address = block.start;
+    while address < block.end {
+      let opcode = OpCode::decode(self.codes, address).unwrap();
+      let next = address + opcode.size();
+      lines.push(format!("// {}: {}", address, opcode));
+      let code = if next == block.end && block.exit != Exit::Next(next) {
+        self.terminator(address, &opcode, next, block.exit)
+      } else {
+        self.instruction(address, &opcode, next)
+      };
+      match code {
+        Some(code) => lines.extend(code),
+        None => {
+          lines.push(format!("self.program.position = {};", address));
+          lines.push("return self.interpret();".into());
+          return lines;
+        }
+      }
+      address = next;
+    }
+    if let Exit::Next(_) | Exit::Invalid = block.exit {
+      lines.push(block.end.to_string());
+    }
+    lines
+  }
+
+  /// The word of operand `n` of the instruction at `at`.
+  fn operand(&self, at: usize, n: usize, p: &ParameterMode) -> String {
+    if self.patched.contains(&(at + n)) {
+      format!("self.program.codes.get({})", at + n)
+    } else {
+      p.word().to_string()
+    }
+  }
+
+  /// Reads a parameter, or `None` if it can never be read.
+  fn read(&self, at: usize, n: usize, p: &ParameterMode) -> Option<String> {
+    let operand = self.operand(at, n, p);
+    match (p, self.patched.contains(&(at + n))) {
+      (ParameterMode::Immediate(_), _) => Some(operand),
+      (ParameterMode::Position(_), true) => Some(format!(
+        "self.program.codes.get(self.address({}, {})?)",
+        at, operand
+      )),
+      (ParameterMode::Position(i), false) if *i >= 0 => {
+        Some(format!("self.program.codes.get({})", i))
+      }
+      (ParameterMode::Position(_), false) => None,
+      (ParameterMode::Relative(_), _) => Some(format!(
+        "self.program.codes.get(self.relative({}, {})?)",
+        at, operand
+      )),
+    }
+  }
+
+  /// Where a parameter writes, or `None` if it can never be written.
+  fn destination(&self, at: usize, n: usize, p: &ParameterMode) -> Option<Destination> {
+    let operand = self.operand(at, n, p);
+    match (p, self.patched.contains(&(at + n))) {
+      (ParameterMode::Immediate(_), _) => None,
+      (ParameterMode::Position(_), true) => Some(Destination::Dynamic(format!(
+        "self.address({}, {})?",
+        at, operand
+      ))),
+      (ParameterMode::Position(i), false) if *i >= 0 => Some(Destination::Fixed(*i as usize)),
+      (ParameterMode::Position(_), false) => None,
+      (ParameterMode::Relative(_), _) => Some(Destination::Dynamic(format!(
+        "self.relative({}, {})?",
+        at, operand
+      ))),
+    }
+  }
+
+  /// Statements storing `value` through operand `n`, or `None` if it can never be written.
+  fn write(
+    &self,
+    at: usize,
+    n: usize,
+    p: &ParameterMode,
+    value: String,
+    next: usize,
+  ) -> Option<Vec<String>> {
+    let destination = match self.destination(at, n, p)? {
+      Destination::Fixed(i) if !self.is_code(i) => {
+        return Some(vec![format!("self.set({}, {});", i, value)])
+      }
+      Destination::Fixed(i) => i.to_string(),
+      Destination::Dynamic(d) => d,
+    };
+    Some(vec![
+      format!("let value = {};", value),
+      format!(
+        "if self.store({}, value) {{ self.program.position = {}; return Ok(None); }}",
+        destination, next
+      ),
+    ])
+  }
+
+  /// Statements for an instruction in the middle of a block, or `None` to leave it to the
+  /// interpreter.
+  fn instruction(&self, at: usize, opcode: &OpCode, next: usize) -> Option<Vec<String>> {
+    let binary = |p1, p2, p3, f: &dyn Fn(String, String) -> String| {
+      let value = f(self.read(at, 1, p1)?, self.read(at, 2, p2)?);
+      self.write(at, 3, p3, value, next)
+    };
+    match opcode {
+      OpCode::Add(p1, p2, p3) => {
+        binary(p1, p2, p3, &|a, b| format!("I::wrapping_add({}, {})", a, b))
+      }
+      OpCode::Multiply(p1, p2, p3) => {
+        binary(p1, p2, p3, &|a, b| format!("I::wrapping_mul({}, {})", a, b))
+      }
+      OpCode::LessThan(p1, p2, p3) => binary(p1, p2, p3, &|a, b| format!("({} < {}) as I", a, b)),
+      OpCode::Equals(p1, p2, p3) => binary(p1, p2, p3, &|a, b| format!("({} == {}) as I", a, b)),
+      OpCode::AdjustRelativeBase(p1) => Some(vec![format!(
+        "self.program.relative_position = self.program.relative_position.wrapping_add({});",
+        self.read(at, 1, p1)?
+      )]),
+      OpCode::Input(p1) => {
+        let destination = match self.destination(at, 1, p1)? {
+          Destination::Fixed(i) => i.to_string(),
+          Destination::Dynamic(d) => d,
+        };
+        Some(vec![
+          format!("let destination = {};", destination),
+          format!("let (event, changed) = self.input({}, destination)?;", at),
+          format!(
+            "if let Some(stop) = self.stop({}, {}, event, changed) {{ return Ok(stop); }}",
+            at, next
+          ),
+        ])
+      }
+      OpCode::Output(p1) => Some(vec![
+        format!(
+          "let event = self.program.system.take_output({});",
+          self.read(at, 1, p1)?
+        ),
+        format!(
+          "if let Some(stop) = self.stop({}, {}, event, false) {{ return Ok(stop); }}",
+          at, next
+        ),
+      ]),
+      // A jump on a constant that never jumps, unless the constant is patched.
+      OpCode::JumpIfTrue(_, _) | OpCode::JumpIfFalse(_, _) if !self.patched.contains(&(at + 1)) => {
+        Some(vec![])
+      }
+      OpCode::JumpIfTrue(_, _) | OpCode::JumpIfFalse(_, _) | OpCode::Break => None,
+      OpCode::Custom { .. } => None,
+    }
+  }
+
+  /// Statements for the instruction ending a block, evaluating to the next position, or `None`
+  /// to leave it to the interpreter.
+  fn terminator(&self, at: usize, opcode: &OpCode, next: usize, exit: Exit) -> Option<Vec<String>> {
+    let (p1, p2, jump_if_zero) = match opcode {
+      OpCode::Break => {
+        return Some(vec![
+          format!("self.program.position = {};", at),
+          "return Ok(Some(Event::Halted));".into(),
+        ])
+      }
+      OpCode::JumpIfTrue(p1, p2) => (p1, p2, false),
+      OpCode::JumpIfFalse(p1, p2) => (p1, p2, true),
+      _ => return self.instruction(at, opcode, next),
+    };
+    if self.patched.contains(&(at + 1)) {
+      return None;
+    }
+    let target = match (exit, self.patched.contains(&(at + 2))) {
+      (Exit::Jump(t), false) | (Exit::Branch { taken: t, .. }, false) => t.to_string(),
+      (
+        Exit::Call {
+          target: Some(t), ..
+        },
+        false,
+      ) => t.to_string(),
+      _ => format!("self.address({}, {})?", at, self.read(at, 2, p2)?),
+    };
+    Some(match exit {
+      Exit::Branch { .. } | Exit::Indirect { next: Some(_) } => {
+        let test = if jump_if_zero { "==" } else { "!=" };
+        let condition = self.read(at, 1, p1)?;
+        vec![format!(
+          "if {} {} 0 {{ {} }} else {{ {} }}",
+          condition, test, target, next
+        )]
+      }
+      _ => vec![target],
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_translate() {
+    // Doubles every input until it reads a zero.
+    let codes = [3, 15, 1006, 15, 14, 1002, 15, 2, 15, 4, 15, 1105, 1, 0, 99];
+    let rust = translate(&codes, &[]);
+    assert!(rust.contains("const CODE: &[(usize, usize)] = &[(0, 15)];"));
+    assert!(rust.contains("const BLOCKS: &[(usize, usize)] = &[(0, 5), (5, 14), (14, 15)];"));
+    let arm = "
+        0 if !self.stale[0] => {
+          // 0: in [15]
+          let destination = 15;
+          let (event, changed) = self.input(0, destination)?;
+          if let Some(stop) = self.stop(0, 2, event, changed) { return Ok(stop); }
+          // 2: jf [15], #14
+          if self.program.codes.get(15) == 0 { 14 } else { 5 }
+        }
+        5 if !self.stale[1] => {
+          // 5: mul [15], #2, [15]
+          self.set(15, I::wrapping_mul(self.program.codes.get(15), 2));
+          // 9: out [15]
+          let event = self.program.system.take_output(self.program.codes.get(15));
+          if let Some(stop) = self.stop(9, 11, event, false) { return Ok(stop); }
+          // 11: jt #1, #0
+          0
+        }
+        14 if !self.stale[2] => {
+          // 14: hlt
+          self.program.position = 14;
+          return Ok(Some(Event::Halted));
+        }
+        _ => {";
+    assert!(rust.contains(arm), "{}", rust);
+  }
+}
@@ -0,0 +1,75 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// A machine word the VM can run on. Opcodes, modes and addresses always fit an `i64`, so
+/// words only need to convert to and from one.
+pub trait Word:
+  Clone + Debug + Display + Default + Eq + Ord + Hash + FromStr + Send + Sync + 'static
+{
+  fn from_i64(i: i64) -> Self;
+  /// The value as an `i64`, if it fits.
+  fn to_i64(&self) -> Option<i64>;
+  fn checked_add(&self, other: &Self) -> Option<Self>;
+  fn checked_mul(&self, other: &Self) -> Option<Self>;
+  fn wrapping_add(&self, other: &Self) -> Self;
+  fn wrapping_mul(&self, other: &Self) -> Self;
+  fn is_zero(&self) -> bool {
+    *self == Self::default()
+  }
+}
+
+macro_rules! primitive_word {
+  ($($t:ty),*) => {
+    $(
+      impl Word for $t {
+        fn from_i64(i: i64) -> Self {
+          i as $t
+        }
+        #[allow(clippy::unnecessary_cast)]
+        fn to_i64(&self) -> Option<i64> {
+          use std::convert::TryFrom;
+          i64::try_from(*self).ok()
+        }
+        fn checked_add(&self, other: &Self) -> Option<Self> {
+          <$t>::checked_add(*self, *other)
+        }
+        fn checked_mul(&self, other: &Self) -> Option<Self> {
+          <$t>::checked_mul(*self, *other)
+        }
+        fn wrapping_add(&self, other: &Self) -> Self {
+          <$t>::wrapping_add(*self, *other)
+        }
+        fn wrapping_mul(&self, other: &Self) -> Self {
+          <$t>::wrapping_mul(*self, *other)
+        }
+      }
+    )*
+  };
+}
+
+primitive_word!(i32, i64, i128);
+
+/// Arbitrary precision words never overflow.
+#[cfg(feature = "bigint")]
+impl Word for num_bigint::BigInt {
+  fn from_i64(i: i64) -> Self {
+    i.into()
+  }
+  fn to_i64(&self) -> Option<i64> {
+    use std::convert::TryFrom;
+    i64::try_from(self).ok()
+  }
+  fn checked_add(&self, other: &Self) -> Option<Self> {
+    Some(self + other)
+  }
+  fn checked_mul(&self, other: &Self) -> Option<Self> {
+    Some(self * other)
+  }
+  fn wrapping_add(&self, other: &Self) -> Self {
+    self + other
+  }
+  fn wrapping_mul(&self, other: &Self) -> Self {
+    self * other
+  }
+}